@@ -1,7 +1,7 @@
 //! Example of advanced usage of the rust-mc-status library
 //! Demonstrates new capabilities of the library
 
-use mc_server_status::{McClient, ServerEdition, ServerInfo};
+use mc_server_status::{McClient, ServerEdition, ServerInfo, ServerState};
 use std::time::Duration;
 
 #[tokio::main]
@@ -12,35 +12,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // List of servers to check
     let servers = vec![
-        ServerInfo {
-            address: "mc.hypixel.net".to_string(),
-            edition: ServerEdition::Java,
-        },
-        ServerInfo {
-            address: "geo.hivebedrock.network:19132".to_string(),
-            edition: ServerEdition::Bedrock,
-        },
-        ServerInfo {
-            address: "mc233.cn".to_string(),
-            edition: ServerEdition::Java,
-        },
+        ServerInfo::new("mc.hypixel.net", ServerEdition::Java),
+        ServerInfo::new("geo.hivebedrock.network:19132", ServerEdition::Bedrock),
+        ServerInfo::new("mc233.cn", ServerEdition::Java),
     ];
 
     println!("Requesting status for {} servers...", servers.len());
     let results = client.ping_many(&servers).await;
 
-    for (server, result) in results {
+    for result in results {
         println!("\n{}", "=".repeat(50));
-        println!("Server: {} ({:?})", server.address, server.edition);
-
-        match result {
-            Ok(status) => {
-                println!("Status: ✅ Online (latency: {:.2} ms)", status.latency);
-                println!("IP: {}, Port: {}", status.ip, status.port);
-                println!("Hostname: {}", status.hostname);
+        println!(
+            "Server: {} ({:?})",
+            result.target.address, result.target.edition
+        );
+
+        match result.state {
+            ServerState::Online(data) => {
+                println!(
+                    "Status: ✅ Online (latency: {:.2} ms, attempts: {})",
+                    result.latency.unwrap_or_default(),
+                    result.attempts
+                );
+                if let Some(address) = result.address {
+                    println!("Address: {}", address);
+                }
 
                 // DNS information
-                if let Some(dns) = status.dns {
+                if let Some(dns) = result.dns {
                     println!(
                         "DNS: A-records: {:?}, CNAME: {:?}",
                         dns.a_records, dns.cname
@@ -48,7 +47,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
 
                 // Processing data depending on server type
-                match status.data {
+                match data {
                     mc_server_status::ServerData::Java(java_status) => {
                         println!(
                             "Version: {} (protocol: {})",
@@ -135,8 +134,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             }
-            Err(e) => {
-                println!("Status: ❌ Error: {}", e);
+            ServerState::Offline { reason } => {
+                println!("Status: ❌ Offline: {}", reason.message);
             }
         }
     }
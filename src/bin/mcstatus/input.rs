@@ -0,0 +1,89 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::io::{self, BufRead};
+use std::path::Path;
+
+use mc_server_status::{McClient, McError, PingResult, ServerEdition, ServerInfo};
+
+/// A server to ping. If the user named no edition, `auto` is set and both are tried.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub server: ServerInfo,
+    pub auto: bool,
+}
+
+impl Target {
+    pub fn new(address: impl Into<String>, edition: Option<ServerEdition>) -> Self {
+        Self {
+            server: ServerInfo::new(address, edition.unwrap_or(ServerEdition::Java)),
+            auto: edition.is_none(),
+        }
+    }
+}
+
+/// Parses `address [edition]`. Blank lines and `#` comments yield `None`.
+fn parse_line(line: &str, default: Option<ServerEdition>) -> Option<Result<Target, McError>> {
+    let line = line.split('#').next().unwrap_or_default().trim();
+    let mut words = line.split_whitespace();
+    let address = words.next()?;
+    let edition = match words.next() {
+        Some(edition) => match edition.parse() {
+            Ok(edition) => Some(edition),
+            Err(error) => return Some(Err(error)),
+        },
+        None => default,
+    };
+    Some(Ok(Target::new(address, edition)))
+}
+
+/// Reads targets from `path`, or from stdin if `path` is `-`.
+pub fn read_targets(
+    path: &Path,
+    default: Option<ServerEdition>,
+) -> Result<Vec<Target>, Box<dyn std::error::Error>> {
+    let lines: Vec<String> = if path == Path::new("-") {
+        io::stdin().lock().lines().collect::<Result<_, _>>()?
+    } else {
+        std::fs::read_to_string(path)?
+            .lines()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let mut targets = Vec::new();
+    for (number, line) in lines.iter().enumerate() {
+        match parse_line(line, default) {
+            Some(Ok(target)) => targets.push(target),
+            Some(Err(error)) => {
+                return Err(format!("{}:{}: {error}", path.display(), number + 1).into());
+            }
+            None => {}
+        }
+    }
+    Ok(targets)
+}
+
+/// Pings `target`, trying both editions at once when none was given. A Java answer wins;
+/// if neither answers, the failure reported is that of the edition the port suggests.
+pub async fn ping(client: &McClient, target: &Target) -> PingResult {
+    if !target.auto {
+        return client.probe(&target.server).await;
+    }
+
+    let java = ServerInfo {
+        edition: ServerEdition::Java,
+        ..target.server.clone()
+    };
+    let bedrock = ServerInfo {
+        edition: ServerEdition::Bedrock,
+        ..target.server.clone()
+    };
+    let (java, bedrock) = futures::join!(client.probe(&java), client.probe(&bedrock));
+    let address = &target.server.address;
+    let bedrock_port = address.ends_with(":19132") || address.ends_with(":19133");
+    if java.is_online() || (!bedrock.is_online() && !bedrock_port) {
+        java
+    } else {
+        bedrock
+    }
+}
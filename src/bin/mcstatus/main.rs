@@ -0,0 +1,236 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! `mcstatus`: ping Minecraft servers from the command line.
+//!
+//! Exits with 0 if every server answered, 1 if any was offline and 2 if any input was
+//! invalid or a server sent an unusable response. `mcstatus watch` keeps pinging and
+//! shows the results in a live table instead.
+
+mod input;
+mod output;
+mod watch;
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use futures::StreamExt;
+use mc_server_status::{McClient, ServerEdition, ServerList};
+
+use input::{Target, read_targets};
+use output::{Format, Outcome, Printer};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "mcstatus",
+    version,
+    about = "Ping Minecraft Java and Bedrock servers",
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    ping: PingArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Keep pinging servers and show them in a live table.
+    Watch(WatchArgs),
+}
+
+#[derive(Debug, Args)]
+struct PingArgs {
+    #[command(flatten)]
+    servers: ServerArgs,
+
+    #[arg(short = 'o', long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Never color the output. Also honors `NO_COLOR`.
+    #[arg(long)]
+    no_color: bool,
+}
+
+#[derive(Debug, Args)]
+struct WatchArgs {
+    #[command(flatten)]
+    servers: ServerArgs,
+
+    /// Read servers from a TOML, JSON or YAML server list.
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Seconds between pings [default: 10].
+    #[arg(short, long, value_parser = seconds)]
+    interval: Option<Duration>,
+}
+
+/// Where the servers come from and how to ping them, shared by every command.
+#[derive(Debug, Args)]
+struct ServerArgs {
+    /// Servers to ping, as `host[:port]`.
+    servers: Vec<String>,
+
+    /// Read servers from a file, one `address [edition]` per line; `-` reads stdin.
+    #[arg(short, long)]
+    file: Option<PathBuf>,
+
+    /// Edition of servers that do not name one.
+    #[arg(short, long, value_enum, default_value_t = EditionArg::Auto)]
+    edition: EditionArg,
+
+    /// Seconds to wait for each server.
+    #[arg(short, long, default_value = "5", value_parser = seconds)]
+    timeout: Duration,
+
+    /// Servers pinged at once.
+    #[arg(short, long, default_value_t = 10, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    parallel: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EditionArg {
+    /// Try both and report whichever answers.
+    Auto,
+    Java,
+    Bedrock,
+}
+
+impl EditionArg {
+    fn edition(self) -> Option<ServerEdition> {
+        match self {
+            EditionArg::Auto => None,
+            EditionArg::Java => Some(ServerEdition::Java),
+            EditionArg::Bedrock => Some(ServerEdition::Bedrock),
+        }
+    }
+}
+
+impl ServerArgs {
+    fn client(&self) -> McClient {
+        McClient::new()
+            .with_timeout(self.timeout)
+            .with_max_parallel(self.parallel)
+    }
+
+    /// The servers named on the command line, then those in `--file`, or on stdin if
+    /// neither was given and stdin is not a terminal.
+    fn targets(&self, stdin: bool) -> Result<Vec<Target>, Box<dyn std::error::Error>> {
+        let default = self.edition.edition();
+        let mut targets: Vec<Target> = self
+            .servers
+            .iter()
+            .map(|address| Target::new(address.clone(), default))
+            .collect();
+
+        if let Some(file) = &self.file {
+            targets.extend(read_targets(file, default)?);
+        } else if stdin && self.servers.is_empty() && !std::io::stdin().is_terminal() {
+            targets.extend(read_targets(&PathBuf::from("-"), default)?);
+        }
+        Ok(targets)
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Some(Command::Watch(args)) => run_watch(args).await.map(|()| Outcome::Online),
+        None => run(cli.ping).await,
+    };
+    match result {
+        Ok(Outcome::Online) => ExitCode::from(0),
+        Ok(Outcome::Offline) => ExitCode::from(1),
+        Ok(Outcome::Error) => ExitCode::from(2),
+        Err(error) => {
+            eprintln!("mcstatus: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+async fn run(args: PingArgs) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let targets = args.servers.targets(true)?;
+    if targets.is_empty() {
+        return Err("no servers given; pass addresses, --file or pipe them on stdin".into());
+    }
+
+    let client = args.servers.client();
+    let color =
+        !args.no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal();
+
+    let mut printer = Printer::new(args.format, color);
+    let mut worst = Outcome::Online;
+    // `buffered` yields in input order, so each result prints as soon as the ones listed
+    // before it are done.
+    let pings: Vec<_> = targets
+        .iter()
+        .map(|target| input::ping(&client, target))
+        .collect();
+    let mut results = futures::stream::iter(pings).buffered(args.servers.parallel);
+    while let Some(result) = results.next().await {
+        worst = worst.max(Outcome::of(&result));
+        printer.print(result)?;
+    }
+    printer.finish()?;
+    Ok(worst)
+}
+
+async fn run_watch(args: WatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // Stdin stays with the keyboard, so servers only come from arguments and files.
+    let mut servers: Vec<(String, Target)> = args
+        .servers
+        .targets(false)?
+        .into_iter()
+        .map(|target| (target.server.address.clone(), target))
+        .collect();
+    let mut interval = None;
+    if let Some(path) = &args.config {
+        let list = ServerList::load(path)?;
+        // One table, one pace: the quickest interval in the list.
+        interval = list.servers.iter().filter_map(|entry| entry.interval).min();
+        servers.extend(list.servers.into_iter().map(|entry| {
+            let target = Target {
+                server: entry.server,
+                auto: false,
+            };
+            (entry.name, target)
+        }));
+    }
+    if servers.is_empty() {
+        return Err("no servers given; pass addresses, --file or --config".into());
+    }
+
+    let interval = args
+        .interval
+        .or(interval)
+        .unwrap_or(watch::DEFAULT_INTERVAL);
+    if !std::io::stdout().is_terminal() {
+        return Err("watch needs a terminal; use plain `mcstatus` to print results".into());
+    }
+    watch::run(
+        args.servers.client(),
+        servers,
+        interval,
+        args.servers.parallel,
+    )
+    .await
+}
+
+/// Parses a positive number of seconds for `--timeout` and `--interval`.
+fn seconds(text: &str) -> Result<Duration, String> {
+    let seconds: f64 = text
+        .parse()
+        .map_err(|_| format!("`{text}` is not a number"))?;
+    if seconds <= 0.0 {
+        return Err("must be more than zero seconds".to_string());
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("`{text}` is out of range"))
+}
@@ -0,0 +1,250 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use mc_server_status::{PingResult, ServerData, ServerEdition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Ndjson,
+    Csv,
+}
+
+/// How a ping went, for the exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    Online,
+    /// The server could not be reached.
+    Offline,
+    /// The input was bad or the server answered with something unusable.
+    Error,
+}
+
+impl Outcome {
+    pub fn of(result: &PingResult) -> Self {
+        match result.offline_reason() {
+            None => Outcome::Online,
+            Some(reason) if reason.offline => Outcome::Offline,
+            Some(_) => Outcome::Error,
+        }
+    }
+}
+
+/// The fields shown for every server, whatever its edition.
+pub struct Summary {
+    pub address: String,
+    pub edition: &'static str,
+    pub outcome: Outcome,
+    pub latency: Option<f64>,
+    pub players: Option<(i64, i64)>,
+    pub version: Option<String>,
+    pub motd: Option<String>,
+    pub error: Option<String>,
+}
+
+impl Summary {
+    pub fn of(result: &PingResult) -> Self {
+        let (players, version, motd) = match result.data() {
+            Some(ServerData::Java(java)) => (
+                Some((java.players.online, java.players.max)),
+                Some(java.version.name.clone()),
+                Some(java.description.clone()),
+            ),
+            Some(ServerData::Bedrock(bedrock)) => (
+                bedrock
+                    .online_players
+                    .parse()
+                    .ok()
+                    .zip(bedrock.max_players.parse().ok()),
+                Some(bedrock.version.clone()),
+                Some(bedrock.motd.clone()),
+            ),
+            None => (None, None, None),
+        };
+
+        Self {
+            address: result.target.address.clone(),
+            edition: match result.target.edition {
+                ServerEdition::Java => "java",
+                ServerEdition::Bedrock => "bedrock",
+            },
+            outcome: Outcome::of(result),
+            latency: result.latency,
+            players,
+            version,
+            motd,
+            error: result.offline_reason().map(|reason| reason.message.clone()),
+        }
+    }
+}
+
+/// Writes results one at a time in any format.
+pub struct Printer {
+    format: Format,
+    color: bool,
+    json: Vec<PingResult>,
+}
+
+impl Printer {
+    pub fn new(format: Format, color: bool) -> Self {
+        if format == Format::Csv {
+            println!(
+                "address,edition,status,latency_ms,players_online,players_max,version,motd,error"
+            );
+        }
+        Self {
+            format,
+            color,
+            json: Vec::new(),
+        }
+    }
+
+    pub fn print(&mut self, result: PingResult) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        match self.format {
+            Format::Text => writeln!(out, "{}", text_line(&Summary::of(&result), self.color)),
+            Format::Ndjson => writeln!(out, "{}", serde_json::to_string(&result)?),
+            Format::Csv => writeln!(out, "{}", csv_line(&Summary::of(&result))),
+            Format::Json => {
+                self.json.push(result);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        if self.format == Format::Json {
+            println!("{}", serde_json::to_string_pretty(&self.json)?);
+        }
+        Ok(())
+    }
+}
+
+fn text_line(summary: &Summary, color: bool) -> String {
+    let paint = |code: &str, text: &str| {
+        if color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    };
+
+    let (mark, status) = match summary.outcome {
+        Outcome::Online => (paint("32", "●"), paint("32", "online ")),
+        Outcome::Offline => (paint("31", "○"), paint("31", "offline")),
+        Outcome::Error => (paint("33", "!"), paint("33", "error  ")),
+    };
+    let mut line = format!(
+        "{mark} {:<30} {:<7} {status}",
+        summary.address, summary.edition
+    );
+
+    if let Some(error) = &summary.error {
+        line.push_str(&format!("  {}", paint("2", error)));
+        return line;
+    }
+    if let Some(latency) = summary.latency {
+        line.push_str(&format!("  {:>5.0} ms", latency));
+    }
+    if let Some((online, max)) = summary.players {
+        line.push_str(&format!("  {:>11}", format!("{online}/{max}")));
+    }
+    if let Some(version) = &summary.version {
+        line.push_str(&format!("  {}", paint("36", version)));
+    }
+    if let Some(motd) = &summary.motd {
+        let motd = motd.lines().map(str::trim).collect::<Vec<_>>().join(" | ");
+        line.push_str(&format!("  {}", render_motd(&motd, color)));
+    }
+    line
+}
+
+fn csv_line(summary: &Summary) -> String {
+    let status = match summary.outcome {
+        Outcome::Online => "online",
+        Outcome::Offline => "offline",
+        Outcome::Error => "error",
+    };
+    [
+        summary.address.clone(),
+        summary.edition.to_string(),
+        status.to_string(),
+        summary
+            .latency
+            .map(|l| format!("{l:.1}"))
+            .unwrap_or_default(),
+        summary.players.map(|p| p.0.to_string()).unwrap_or_default(),
+        summary.players.map(|p| p.1.to_string()).unwrap_or_default(),
+        summary.version.clone().unwrap_or_default(),
+        summary
+            .motd
+            .as_deref()
+            .map(|motd| render_motd(motd, false))
+            .unwrap_or_default(),
+        summary.error.clone().unwrap_or_default(),
+    ]
+    .iter()
+    .map(|field| csv_field(field))
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Turns `§` formatting codes into ANSI escapes, or strips them when `color` is off.
+pub fn render_motd(motd: &str, color: bool) -> String {
+    let mut out = String::new();
+    let mut chars = motd.chars();
+    while let Some(c) = chars.next() {
+        if c != '§' {
+            out.push(c);
+            continue;
+        }
+        let Some(code) = chars.next() else { break };
+        if !color {
+            continue;
+        }
+        let ansi = match code.to_ascii_lowercase() {
+            '0' => "30",
+            '1' => "34",
+            '2' => "32",
+            '3' => "36",
+            '4' => "31",
+            '5' => "35",
+            '6' => "33",
+            '7' => "37",
+            '8' => "90",
+            '9' => "94",
+            'a' => "92",
+            'b' => "96",
+            'c' => "91",
+            'd' => "95",
+            'e' => "93",
+            'f' => "97",
+            'l' => "1",
+            'm' => "9",
+            'n' => "4",
+            'o' => "3",
+            'r' => "0",
+            _ => continue,
+        };
+        // A color code also clears bold and the like, as in game.
+        if code.is_ascii_hexdigit() {
+            out.push_str("\x1b[0m");
+        }
+        out.push_str(&format!("\x1b[{ansi}m"));
+    }
+    if color && motd.contains('§') {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
@@ -0,0 +1,527 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! The live table behind `mcstatus watch`.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue, style::Print};
+use futures::StreamExt;
+use mc_server_status::{McClient, PingResult};
+use tokio::sync::{Notify, mpsc};
+
+use crate::input::{self, Target};
+use crate::output::{Outcome, Summary, render_motd};
+
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+/// Latency samples kept per server, one sparkline cell each.
+const HISTORY: usize = 20;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const MAX_NAME_WIDTH: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    /// The order the servers were given in.
+    Input,
+    Name,
+    Status,
+    Latency,
+    Players,
+}
+
+impl SortKey {
+    fn next(self) -> Self {
+        match self {
+            SortKey::Input => SortKey::Name,
+            SortKey::Name => SortKey::Status,
+            SortKey::Status => SortKey::Latency,
+            SortKey::Latency => SortKey::Players,
+            SortKey::Players => SortKey::Input,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortKey::Input => "input",
+            SortKey::Name => "name",
+            SortKey::Status => "status",
+            SortKey::Latency => "latency",
+            SortKey::Players => "players",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    All,
+    Online,
+    /// Offline or erroring.
+    Down,
+}
+
+impl Filter {
+    fn next(self) -> Self {
+        match self {
+            Filter::All => Filter::Online,
+            Filter::Online => Filter::Down,
+            Filter::Down => Filter::All,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Filter::All => "all",
+            Filter::Online => "online",
+            Filter::Down => "down",
+        }
+    }
+}
+
+/// One server's line in the table.
+struct Row {
+    index: usize,
+    name: String,
+    summary: Option<Summary>,
+    /// Recent latencies, oldest first; `None` for pings that failed.
+    latencies: VecDeque<Option<f64>>,
+}
+
+impl Row {
+    fn outcome(&self) -> Option<Outcome> {
+        self.summary.as_ref().map(|summary| summary.outcome)
+    }
+
+    fn latency(&self) -> Option<f64> {
+        self.summary.as_ref().and_then(|summary| summary.latency)
+    }
+
+    fn players(&self) -> Option<i64> {
+        self.summary
+            .as_ref()
+            .and_then(|summary| summary.players)
+            .map(|(online, _)| online)
+    }
+
+    fn matches(&self, search: &str) -> bool {
+        if search.is_empty() {
+            return true;
+        }
+        let search = search.to_lowercase();
+        let mut fields = vec![self.name.clone()];
+        if let Some(summary) = &self.summary {
+            fields.push(summary.address.clone());
+            fields.extend(summary.version.clone());
+            fields.extend(summary.motd.as_deref().map(|motd| render_motd(motd, false)));
+        }
+        fields
+            .iter()
+            .any(|field| field.to_lowercase().contains(&search))
+    }
+}
+
+struct Dashboard {
+    rows: Vec<Row>,
+    interval: Duration,
+    sort: SortKey,
+    reverse: bool,
+    filter: Filter,
+    search: String,
+    /// Whether keys are being typed into the search.
+    searching: bool,
+    last_result: Option<Instant>,
+}
+
+impl Dashboard {
+    fn record(&mut self, index: usize, result: PingResult) {
+        let row = &mut self.rows[index];
+        if row.latencies.len() == HISTORY {
+            row.latencies.pop_front();
+        }
+        row.latencies
+            .push_back(result.latency.filter(|_| result.is_online()));
+        row.summary = Some(Summary::of(&result));
+        self.last_result = Some(Instant::now());
+    }
+
+    /// Handles a key press, returning `false` when it is time to quit.
+    fn key(&mut self, key: KeyEvent, refresh: &Notify) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+
+        if self.searching {
+            match key.code {
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.search.clear();
+                }
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Char(c) => self.search.push(c),
+                _ => {}
+            }
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('s') => self.sort = self.sort.next(),
+            KeyCode::Char('r') => self.reverse = !self.reverse,
+            KeyCode::Char('f') => self.filter = self.filter.next(),
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.search.clear();
+            }
+            KeyCode::Char('p') => refresh.notify_one(),
+            _ => {}
+        }
+        true
+    }
+
+    /// The rows to show, filtered and sorted.
+    fn visible(&self) -> Vec<&Row> {
+        let mut rows: Vec<&Row> = self
+            .rows
+            .iter()
+            .filter(|row| match self.filter {
+                Filter::All => true,
+                Filter::Online => row.outcome() == Some(Outcome::Online),
+                Filter::Down => matches!(row.outcome(), Some(Outcome::Offline | Outcome::Error)),
+            })
+            .filter(|row| row.matches(&self.search))
+            .collect();
+
+        rows.sort_by(|a, b| {
+            let order = match self.sort {
+                SortKey::Input => Ordering::Equal,
+                SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                // Servers not yet pinged go last.
+                SortKey::Status => a
+                    .outcome()
+                    .is_none()
+                    .cmp(&b.outcome().is_none())
+                    .then(a.outcome().cmp(&b.outcome())),
+                SortKey::Latency => a.latency().is_none().cmp(&b.latency().is_none()).then(
+                    a.latency()
+                        .partial_cmp(&b.latency())
+                        .unwrap_or(Ordering::Equal),
+                ),
+                // Busiest first.
+                SortKey::Players => b.players().cmp(&a.players()),
+            };
+            order.then(a.index.cmp(&b.index))
+        });
+        if self.reverse {
+            rows.reverse();
+        }
+        rows
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let rows = self.visible();
+
+        let online = self
+            .rows
+            .iter()
+            .filter(|row| row.outcome() == Some(Outcome::Online))
+            .count();
+        let updated = match self.last_result {
+            Some(at) => format!("updated {}s ago", at.elapsed().as_secs()),
+            None => "waiting for first results".to_string(),
+        };
+        let mut lines = vec![
+            format!(
+                "\x1b[1mmcstatus watch\x1b[0m  {online}/{} online  every {}s  {updated}",
+                self.rows.len(),
+                self.interval.as_secs_f64(),
+            ),
+            format!(
+                "sort: {}{}  filter: {}{}",
+                self.sort.label(),
+                if self.reverse { " (reversed)" } else { "" },
+                self.filter.label(),
+                if self.search.is_empty() {
+                    String::new()
+                } else {
+                    format!("  search: {}", self.search)
+                },
+            ),
+            String::new(),
+        ];
+
+        let name_width = self
+            .rows
+            .iter()
+            .map(|row| row.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .clamp(4, MAX_NAME_WIDTH);
+        lines.push(format!(
+            "\x1b[2m  {:<name_width$}  {:<7}  {:<7}  {:>7}  {:<HISTORY$}  {:>11}  {:<16}  MOTD\x1b[0m",
+            "NAME", "EDITION", "STATUS", "LATENCY", "HISTORY", "PLAYERS", "VERSION"
+        ));
+        // Header lines above, key help below.
+        let room = height.saturating_sub(lines.len() + 2);
+        for row in rows.iter().take(room) {
+            lines.push(row_line(row, name_width, width));
+        }
+        if rows.len() > room {
+            lines.push(format!("\x1b[2m  … {} more\x1b[0m", rows.len() - room));
+        }
+
+        queue!(out, cursor::MoveTo(0, 0))?;
+        for (y, line) in lines.iter().enumerate() {
+            queue!(
+                out,
+                cursor::MoveTo(0, y as u16),
+                Print(fit(line, width)),
+                terminal::Clear(ClearType::UntilNewLine)
+            )?;
+        }
+        queue!(out, terminal::Clear(ClearType::FromCursorDown))?;
+
+        let footer = if self.searching {
+            format!("/{}▏  enter to keep, esc to clear", self.search)
+        } else {
+            "[s]ort  [r]everse  [f]ilter  [/] search  [p]ing now  [q]uit".to_string()
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            Print(format!("\x1b[7m{}\x1b[0m", fit(&footer, width))),
+            terminal::Clear(ClearType::UntilNewLine)
+        )?;
+        out.flush()
+    }
+}
+
+fn row_line(row: &Row, name_width: usize, width: usize) -> String {
+    let name = fit(&row.name, name_width);
+    let pad = name_width.saturating_sub(name.chars().count());
+    let Some(summary) = &row.summary else {
+        return format!("\x1b[2m… {name}{:pad$}  {:<7}  pending\x1b[0m", "", "");
+    };
+
+    let (color, mark, status) = match summary.outcome {
+        Outcome::Online => ("32", '●', "online"),
+        Outcome::Offline => ("31", '○', "offline"),
+        Outcome::Error => ("33", '!', "error"),
+    };
+    let latency = summary
+        .latency
+        .filter(|_| summary.outcome == Outcome::Online)
+        .map(|latency| format!("{latency:.0} ms"))
+        .unwrap_or_default();
+    let players = summary
+        .players
+        .map(|(online, max)| format!("{online}/{max}"))
+        .unwrap_or_default();
+    let mut line = format!(
+        "\x1b[{color}m{mark}\x1b[0m {name}{:pad$}  {:<7}  \x1b[{color}m{status:<7}\x1b[0m  {latency:>7}  {}  {players:>11}",
+        "",
+        summary.edition,
+        sparkline(&row.latencies),
+    );
+
+    // What is left of the line goes to the version and MOTD, or to the error.
+    let used = 2 + name_width + 2 + 7 + 2 + 7 + 2 + 7 + 2 + HISTORY + 2 + 11;
+    let rest = width.saturating_sub(used + 2);
+    if let Some(error) = &summary.error {
+        line.push_str(&format!("  \x1b[2m{}\x1b[0m", fit(error, rest)));
+        return line;
+    }
+    let version = render_motd(summary.version.as_deref().unwrap_or_default(), false);
+    line.push_str(&format!("  \x1b[36m{:<16}\x1b[0m", fit(&version, 16)));
+    if let Some(motd) = &summary.motd {
+        let motd = motd.lines().map(str::trim).collect::<Vec<_>>().join(" | ");
+        let room = rest.saturating_sub(18);
+        line.push_str(&format!(
+            "  {}",
+            render_motd(&truncate_motd(&motd, room), true)
+        ));
+    }
+    line
+}
+
+/// One block per sample, scaled between the lowest and highest latency shown.
+fn sparkline(latencies: &VecDeque<Option<f64>>) -> String {
+    let known = latencies.iter().flatten();
+    let low = known.clone().copied().fold(f64::INFINITY, f64::min);
+    let high = known.copied().fold(0.0, f64::max);
+
+    let mut line = " ".repeat(HISTORY - latencies.len());
+    for latency in latencies {
+        match latency {
+            Some(latency) => {
+                let level = if high > low {
+                    ((latency - low) / (high - low) * (SPARKS.len() - 1) as f64).round() as usize
+                } else {
+                    SPARKS.len() / 2
+                };
+                line.push(SPARKS[level.min(SPARKS.len() - 1)]);
+            }
+            None => line.push_str("\x1b[31m·\x1b[0m"),
+        }
+    }
+    line
+}
+
+/// Cuts `text` to `width` characters, not counting ANSI escapes, which are kept.
+fn fit(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut shown = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            out.push(c);
+            for c in chars.by_ref() {
+                out.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        if shown == width {
+            break;
+        }
+        out.push(c);
+        shown += 1;
+    }
+    out
+}
+
+/// Cuts a MOTD to `width` visible characters, keeping its `§` codes intact.
+fn truncate_motd(motd: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut shown = 0;
+    let mut chars = motd.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            out.push(c);
+            out.extend(chars.next());
+            continue;
+        }
+        if shown == width {
+            break;
+        }
+        out.push(c);
+        shown += 1;
+    }
+    out
+}
+
+/// Raw mode on an alternate screen, put back however the dashboard exits.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Pings `servers` every `interval` and redraws the table until the user quits.
+pub async fn run(
+    client: McClient,
+    servers: Vec<(String, Target)>,
+    interval: Duration,
+    parallel: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut dashboard = Dashboard {
+        rows: servers
+            .iter()
+            .enumerate()
+            .map(|(index, (name, _))| Row {
+                index,
+                name: name.clone(),
+                summary: None,
+                latencies: VecDeque::with_capacity(HISTORY),
+            })
+            .collect(),
+        interval,
+        sort: SortKey::Input,
+        reverse: false,
+        filter: Filter::All,
+        search: String::new(),
+        searching: false,
+        last_result: None,
+    };
+
+    let targets: Vec<Target> = servers.into_iter().map(|(_, target)| target).collect();
+    let refresh = Arc::new(Notify::new());
+    let (tx, mut results) = mpsc::unbounded_channel();
+    let pinger = tokio::spawn({
+        let refresh = refresh.clone();
+        async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = refresh.notified() => ticker.reset(),
+                }
+                let pings: Vec<_> = targets
+                    .iter()
+                    .enumerate()
+                    .map(|(index, target)| {
+                        let client = &client;
+                        async move { (index, input::ping(client, target).await) }
+                    })
+                    .collect();
+                let mut round = futures::stream::iter(pings).buffer_unordered(parallel);
+                while let Some(result) = round.next().await {
+                    if tx.send(result).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+
+    let screen = Screen::enter()?;
+    let mut out = io::stdout();
+    let mut events = EventStream::new();
+    // Keeps the "updated ago" clock moving between results.
+    let mut clock = tokio::time::interval(Duration::from_secs(1));
+    let outcome = loop {
+        if let Err(error) = dashboard.draw(&mut out) {
+            break Err(error);
+        }
+        tokio::select! {
+            Some((index, result)) = results.recv() => dashboard.record(index, result),
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    if !dashboard.key(key, &refresh) {
+                        break Ok(());
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(error)) => break Err(error),
+                None => break Ok(()),
+            },
+            _ = clock.tick() => {}
+        }
+    };
+
+    pinger.abort();
+    drop(screen);
+    Ok(outcome?)
+}
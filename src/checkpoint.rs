@@ -0,0 +1,366 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Saved progress of long batch pings and scans, so they can resume after a restart.
+//!
+//! A checkpoint file is NDJSON: a header naming the job, then one line per finished slot.
+//! Running jobs only append to it, so saving costs the same however far along they are.
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::McError;
+use crate::files::write_atomic;
+use crate::models::PingResult;
+use crate::scan::ScanOrder;
+
+/// Progress of a batch job over numbered target slots.
+///
+/// Slots are numbered in the job's deterministic target order, so a resumed job can skip
+/// exactly the ones already done. Pings that were in flight when the job stopped count
+/// as pending and are repeated.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// Identifies the target list the checkpoint belongs to.
+    pub fingerprint: String,
+    /// Target order of a scan, kept so a resumed scan visits targets in the same order.
+    pub order: Option<ScanOrder>,
+    pub total: u64,
+    /// Every slot below this one is done.
+    pub done_before: u64,
+    /// Done slots at or above `done_before`, which finished out of order.
+    pub done_after: BTreeSet<u64>,
+    /// Kept results, in slot order when loaded.
+    pub results: Vec<CheckpointResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointResult {
+    pub slot: u64,
+    pub result: PingResult,
+}
+
+/// First line of a checkpoint file.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    fingerprint: String,
+    order: Option<ScanOrder>,
+    total: u64,
+    done_before: u64,
+}
+
+/// Any later line: a finished slot, with its result if the job keeps it.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    slot: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<PingResult>,
+}
+
+impl Checkpoint {
+    fn new(fingerprint: String, total: u64, order: Option<ScanOrder>) -> Self {
+        Self {
+            fingerprint,
+            order,
+            total,
+            done_before: 0,
+            done_after: BTreeSet::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Reads a checkpoint file, returning `None` if it does not exist.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Option<Self>, McError> {
+        match fs::read_to_string(path).await {
+            Ok(text) => Self::parse(&text).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the checkpoint in compact form, replacing the file at `path`.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), McError> {
+        let bytes = self.to_bytes()?;
+        let path = path.as_ref().to_path_buf();
+        tokio::task::spawn_blocking(move || write_atomic(&path, &bytes))
+            .await
+            .map_err(io::Error::other)??;
+        Ok(())
+    }
+
+    /// Number of slots done.
+    pub fn completed(&self) -> u64 {
+        self.done_before + self.done_after.len() as u64
+    }
+
+    pub fn is_done(&self, slot: u64) -> bool {
+        slot < self.done_before || self.done_after.contains(&slot)
+    }
+
+    fn parse(text: &str) -> Result<Self, McError> {
+        let mut lines = text.lines();
+        let header: Header = serde_json::from_str(lines.next().unwrap_or_default())?;
+        let mut checkpoint = Self::new(header.fingerprint, header.total, header.order);
+        checkpoint.done_before = header.done_before;
+
+        // A line cut short by a crash does not parse; its slot is simply pinged again.
+        for record in lines.filter_map(|line| serde_json::from_str::<Record>(line).ok()) {
+            checkpoint.mark_done(record.slot);
+            if let Some(result) = record.result {
+                checkpoint.results.push(CheckpointResult {
+                    slot: record.slot,
+                    result,
+                });
+            }
+        }
+        // A cancelled write may have been repeated when the job stopped.
+        checkpoint.results.sort_by_key(|r| r.slot);
+        checkpoint.results.dedup_by_key(|r| r.slot);
+        Ok(checkpoint)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, McError> {
+        let header = Header {
+            fingerprint: self.fingerprint.clone(),
+            order: self.order,
+            total: self.total,
+            done_before: self.done_before,
+        };
+        let mut bytes = serde_json::to_vec(&header)?;
+        bytes.push(b'\n');
+        for r in &self.results {
+            serde_json::to_writer(&mut bytes, r)?;
+            bytes.push(b'\n');
+        }
+        for slot in &self.done_after {
+            serde_json::to_writer(
+                &mut bytes,
+                &Record {
+                    slot: *slot,
+                    result: None,
+                },
+            )?;
+            bytes.push(b'\n');
+        }
+        Ok(bytes)
+    }
+
+    fn mark_done(&mut self, slot: u64) {
+        if self.is_done(slot) {
+            return;
+        }
+        self.done_after.insert(slot);
+        while self.done_after.remove(&self.done_before) {
+            self.done_before += 1;
+        }
+    }
+}
+
+/// A checkpoint being updated by a running job. Finished slots are appended to the file
+/// every `interval`, and when dropped before [`finish`](Self::finish) so a cancelled job
+/// loses nothing.
+pub(crate) struct Progress {
+    pub(crate) checkpoint: Checkpoint,
+    path: PathBuf,
+    file: File,
+    /// Lines not yet appended to the file.
+    pending: Vec<u8>,
+    interval: Duration,
+    last_saved: Instant,
+    finished: bool,
+}
+
+impl Progress {
+    /// Resumes the checkpoint at `path`, or starts a new one if there is none. `order` is
+    /// only used for a new checkpoint.
+    pub(crate) async fn open(
+        path: &Path,
+        targets: &str,
+        total: u64,
+        order: Option<ScanOrder>,
+        interval: Duration,
+    ) -> Result<Self, McError> {
+        let fingerprint = fingerprint(targets);
+        let checkpoint = match Checkpoint::load(path).await? {
+            Some(checkpoint)
+                if checkpoint.fingerprint == fingerprint && checkpoint.total == total =>
+            {
+                checkpoint
+            }
+            Some(_) => {
+                return Err(McError::Checkpoint(format!(
+                    "{} was written for a different target list",
+                    path.display()
+                )));
+            }
+            None => Checkpoint::new(fingerprint, total, order),
+        };
+        // Compacting first also drops any half-written last line, so appends start on a
+        // line of their own.
+        checkpoint.save(path).await?;
+        let file = OpenOptions::new().append(true).open(path).await?;
+
+        Ok(Self {
+            checkpoint,
+            path: path.to_path_buf(),
+            file,
+            pending: Vec::new(),
+            interval,
+            last_saved: Instant::now(),
+            finished: false,
+        })
+    }
+
+    /// Marks `slot` done, keeping `result` if given, and saves if the interval has passed.
+    pub(crate) async fn record(
+        &mut self,
+        slot: u64,
+        result: Option<PingResult>,
+    ) -> Result<(), McError> {
+        let record = Record { slot, result };
+        serde_json::to_writer(&mut self.pending, &record)?;
+        self.pending.push(b'\n');
+
+        self.checkpoint.mark_done(slot);
+        if let Some(result) = record.result {
+            self.checkpoint
+                .results
+                .push(CheckpointResult { slot, result });
+        }
+
+        if self.last_saved.elapsed() >= self.interval {
+            self.file.write_all(&self.pending).await?;
+            self.file.flush().await?;
+            self.pending.clear();
+            self.last_saved = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Deletes the checkpoint file now that the job is complete.
+    pub(crate) async fn finish(mut self) -> Result<Checkpoint, McError> {
+        self.finished = true;
+        match fs::remove_file(&self.path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(std::mem::replace(
+            &mut self.checkpoint,
+            Checkpoint::new(String::new(), 0, None),
+        ))
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.finished || self.pending.is_empty() {
+            return;
+        }
+        // The leading newline keeps these lines apart from a record a cancelled write
+        // may have left unfinished.
+        let mut lines = vec![b'\n'];
+        lines.append(&mut self.pending);
+        let path = std::mem::take(&mut self.path);
+        let append = move || {
+            let _ = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(&lines));
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(append)),
+            Err(_) => append(),
+        }
+    }
+}
+
+/// FNV-1a, chosen because it is stable across Rust versions, unlike `DefaultHasher`.
+fn fingerprint(targets: &str) -> String {
+    let hash = targets
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::random_u64;
+    use std::time::SystemTime;
+
+    use crate::models::{ServerEdition, ServerInfo};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mc-checkpoint-{name}-{:016x}", random_u64()))
+    }
+
+    fn result(address: &str) -> PingResult {
+        let target = ServerInfo::new(address, ServerEdition::Java);
+        let error = McError::InvalidAddress(address.to_string());
+        PingResult::new(target, SystemTime::now(), 1, Err(error))
+    }
+
+    #[tokio::test]
+    async fn resume_skips_completed_slots() {
+        let path = temp_path("resume");
+        let mut progress = Progress::open(&path, "targets", 6, None, Duration::ZERO)
+            .await
+            .unwrap();
+        for slot in [0, 1, 4] {
+            progress
+                .record(slot, Some(result(&format!("host{slot}"))))
+                .await
+                .unwrap();
+        }
+        drop(progress);
+
+        let progress = Progress::open(&path, "targets", 6, None, Duration::ZERO)
+            .await
+            .unwrap();
+        let pending: Vec<u64> = (0..6)
+            .filter(|slot| !progress.checkpoint.is_done(*slot))
+            .collect();
+        assert_eq!(pending, [2, 3, 5]);
+        assert_eq!(progress.checkpoint.done_before, 2);
+        let slots: Vec<u64> = progress.checkpoint.results.iter().map(|r| r.slot).collect();
+        assert_eq!(slots, [0, 1, 4]);
+
+        progress.finish().await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn rejects_other_target_lists() {
+        let path = temp_path("fingerprint");
+        let progress = Progress::open(&path, "targets", 3, None, Duration::ZERO)
+            .await
+            .unwrap();
+        drop(progress);
+
+        let error = Progress::open(&path, "other targets", 3, None, Duration::ZERO)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), crate::error::ErrorKind::Checkpoint);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn ignores_torn_and_repeated_records() {
+        let header = r#"{"fingerprint":"f","order":null,"total":5,"done_before":1}"#;
+        let text = format!("{header}\n{{\"slot\":3}}\n{{\"slot\":3}}\n{{\"slot\":1}}\n{{\"slo");
+        let checkpoint = Checkpoint::parse(&text).unwrap();
+
+        assert_eq!(checkpoint.done_before, 2);
+        assert_eq!(checkpoint.done_after, BTreeSet::from([3]));
+        assert_eq!(checkpoint.completed(), 3);
+    }
+}
@@ -1,35 +1,68 @@
 // Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
 
-use std::io::Cursor;
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
-use dashmap::DashMap;
-use once_cell::sync::Lazy;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpStream, UdpSocket};
-use tokio::time::timeout;
-use trust_dns_resolver::TokioAsyncResolver;
-use trust_dns_resolver::config::*;
 
-use crate::error::McError;
+use crate::checkpoint::Progress;
+use crate::error::{DnsErrorKind, McError, Phase};
+use crate::lan::{self, BedrockDiscovery};
+use crate::limits::{Limiter, RateLimits};
 use crate::models::*;
+use crate::protocol::{FrameDecoder, RawPacket, bedrock, java};
+use crate::retry::RetryPolicy;
+use crate::scan::Scan;
+use crate::timeouts::{Budget, Timeouts};
+use crate::transport::{Stream, TokioTransport, Transport};
+
+const DEFAULT_MAX_RESPONSE_SIZE: usize = 2 * 1024 * 1024; // 2 MiB
+const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How far the latest attempt got in resolving the server, so failures after resolution
+/// can still report the address and DNS records.
+#[derive(Default)]
+struct Resolved {
+    address: Option<SocketAddr>,
+    dns: Option<DnsInfo>,
+}
 
-static DNS_CACHE: Lazy<DashMap<String, (SocketAddr, SystemTime)>> = Lazy::new(DashMap::new);
-static SRV_CACHE: Lazy<DashMap<String, (String, u16, SystemTime)>> = Lazy::new(DashMap::new);
-const DNS_CACHE_TTL: u64 = 300; // 5 minutes
+impl Resolved {
+    fn record(found: &Mutex<Resolved>, address: SocketAddr, dns: &Option<DnsInfo>) {
+        if let Ok(mut found) = found.lock() {
+            found.address = Some(address);
+            found.dns = dns.clone();
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct McClient {
-    timeout: Duration,
+    timeouts: Timeouts,
     max_parallel: usize,
+    max_response_size: usize,
+    checkpoint_interval: Duration,
+    java_retry: RetryPolicy,
+    bedrock_retry: RetryPolicy,
+    limiter: Arc<Limiter>,
+    transport: Arc<dyn Transport>,
 }
 
 impl Default for McClient {
     fn default() -> Self {
         Self {
-            timeout: Duration::from_secs(10),
+            timeouts: Timeouts::default(),
             max_parallel: 10,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            java_retry: RetryPolicy::none(),
+            bedrock_retry: RetryPolicy::none(),
+            limiter: Arc::new(Limiter::default()),
+            transport: Arc::new(TokioTransport::new()),
         }
     }
 }
@@ -39,8 +72,39 @@ impl McClient {
         Self::default()
     }
 
+    /// Use the same limit for DNS, connect and each read or write, keeping the overall
+    /// deadline (30 seconds unless changed with [`with_deadline`](Self::with_deadline)).
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = timeout;
+        self.timeouts = Timeouts {
+            total: self.timeouts.total,
+            ..Timeouts::uniform(timeout)
+        };
+        self
+    }
+
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    pub fn with_dns_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.dns = timeout;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.connect = timeout;
+        self
+    }
+
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.read = timeout;
+        self
+    }
+
+    /// Overall deadline for a whole ping, including DNS and retries.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.timeouts.total = Some(deadline);
         self
     }
 
@@ -49,50 +113,203 @@ impl McClient {
         self
     }
 
+    /// Largest Java status packet, in bytes, the client accepts before giving up on a
+    /// server. Defaults to 2 MiB, which leaves room for large favicons and mod lists.
+    pub fn with_max_response_size(mut self, max_response_size: usize) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    /// Use the same retry policy for both Java and Bedrock pings.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.java_retry = policy.clone();
+        self.bedrock_retry = policy;
+        self
+    }
+
+    pub fn with_java_retry(mut self, policy: RetryPolicy) -> Self {
+        self.java_retry = policy;
+        self
+    }
+
+    pub fn with_bedrock_retry(mut self, policy: RetryPolicy) -> Self {
+        self.bedrock_retry = policy;
+        self
+    }
+
+    /// How often resumable jobs append finished pings to their checkpoint. `Duration::ZERO`
+    /// saves after every ping, so even a crash repeats no work.
+    pub fn with_checkpoint_interval(mut self, interval: Duration) -> Self {
+        self.checkpoint_interval = interval;
+        self
+    }
+
+    /// Apply per-host politeness limits. The limiter state is shared by all clones of
+    /// this client, so concurrent `ping_many` calls draw from the same budget.
+    pub fn with_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.limiter = Arc::new(Limiter::new(limits));
+        self
+    }
+
+    /// Send pings through `transport` instead of the real network, e.g. a
+    /// [`MemoryTransport`](crate::transport::MemoryTransport) in tests.
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
     pub async fn ping(
         &self,
         address: &str,
         edition: ServerEdition,
     ) -> Result<ServerStatus, McError> {
-        match edition {
-            ServerEdition::Java => self.ping_java(address).await,
-            ServerEdition::Bedrock => self.ping_bedrock(address).await,
-        }
+        self.ping_counted(address, None, edition, self.timeouts)
+            .await
+            .0
     }
 
     pub async fn ping_java(&self, address: &str) -> Result<ServerStatus, McError> {
-        let start = SystemTime::now();
+        self.ping(address, ServerEdition::Java).await
+    }
+
+    pub async fn ping_bedrock(&self, address: &str) -> Result<ServerStatus, McError> {
+        self.ping(address, ServerEdition::Bedrock).await
+    }
+
+    /// Ping a server and report the outcome as a [`PingResult`], which describes offline
+    /// servers as a regular state instead of an error. Timeouts set on `server` take
+    /// precedence over the client's, and its virtual host is sent in the Java handshake.
+    pub async fn probe(&self, server: &ServerInfo) -> PingResult {
+        let started_at = SystemTime::now();
+        let timeouts = server.timeouts.unwrap_or(self.timeouts);
+        let (result, attempts, resolved) = self
+            .ping_counted(
+                &server.address,
+                server.virtual_host.as_deref(),
+                server.edition,
+                timeouts,
+            )
+            .await;
+        let mut ping = PingResult::new(server.clone(), started_at, attempts, result);
+        if !ping.is_online() {
+            ping.address = resolved.address.or(ping.address);
+            ping.dns = resolved.dns;
+        }
+        ping
+    }
+
+    async fn ping_counted(
+        &self,
+        address: &str,
+        virtual_host: Option<&str>,
+        edition: ServerEdition,
+        timeouts: Timeouts,
+    ) -> (Result<ServerStatus, McError>, u32, Resolved) {
+        let budget = Budget::start(timeouts);
+        let resolved = Mutex::new(Resolved::default());
+        let (result, attempts) = match edition {
+            ServerEdition::Java => {
+                self.retry(&self.java_retry, &budget, || {
+                    self.ping_java_once(address, virtual_host, &budget, &resolved)
+                })
+                .await
+            }
+            ServerEdition::Bedrock => {
+                self.retry(&self.bedrock_retry, &budget, || {
+                    self.ping_bedrock_once(address, &budget, &resolved)
+                })
+                .await
+            }
+        };
+        let resolved = resolved.into_inner().unwrap_or_else(|e| e.into_inner());
+        (result, attempts, resolved)
+    }
+
+    async fn retry<F, Fut>(
+        &self,
+        policy: &RetryPolicy,
+        budget: &Budget,
+        mut ping: F,
+    ) -> (Result<ServerStatus, McError>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<ServerStatus, McError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match ping().await {
+                Ok(mut status) => {
+                    status.attempts = attempt;
+                    return (Ok(status), attempt);
+                }
+                Err(e) if policy.should_retry(&e, attempt) => {
+                    let backoff = policy.backoff(attempt);
+                    if budget.remaining().is_some_and(|left| left <= backoff) {
+                        return (Err(e), attempt);
+                    }
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(e) => return (Err(e), attempt),
+            }
+        }
+    }
+
+    async fn ping_java_once(
+        &self,
+        address: &str,
+        virtual_host: Option<&str>,
+        budget: &Budget,
+        found: &Mutex<Resolved>,
+    ) -> Result<ServerStatus, McError> {
         let (host, port, explicit_port) = Self::parse_address_with_flag(address, 25565)?;
 
         // If no explicit port was given, try SRV lookup
-        let (final_host, final_port) = if !explicit_port {
-            self.resolve_srv(host, port)
+        let (final_host, final_port) = if !explicit_port && host.parse::<IpAddr>().is_err() {
+            self.resolve_srv(host, port, budget)
                 .await
                 .unwrap_or((host.to_string(), port))
         } else {
             (host.to_string(), port)
         };
 
-        let resolved = self.resolve_dns(&final_host, final_port).await?;
-        let dns_info = self.get_dns_info(&final_host).await.ok(); // DNS info is optional
+        let resolved = self.resolve_dns(&final_host, final_port, budget).await?;
+        let dns_info = self.get_dns_info(&final_host, budget).await.ok(); // DNS info is optional
+        Resolved::record(found, resolved, &dns_info);
 
-        let mut stream = timeout(self.timeout, TcpStream::connect(resolved))
-            .await
-            .map_err(|_| McError::Timeout)?
-            .map_err(|e| McError::ConnectionError(e.to_string()))?;
-
-        stream.set_nodelay(true).map_err(McError::IoError)?;
+        // Waiting for the rate limiter is not part of connecting.
+        let _permit = budget
+            .until_deadline(
+                Phase::Connect,
+                Some(resolved),
+                self.limiter.acquire(resolved.ip()),
+            )
+            .await?;
+        let start = SystemTime::now();
+        let mut stream = budget
+            .limit(
+                Phase::Connect,
+                Some(resolved),
+                self.transport.connect_tcp(resolved),
+            )
+            .await?
+            .map_err(|e| McError::connection(resolved, Phase::Connect, e))?;
 
-        // Send handshake with the final host and port
-        self.send_handshake(&mut stream, &final_host, final_port)
+        // Send handshake with the virtual host, or else the final host, and port
+        let handshake_host = virtual_host.unwrap_or(&final_host);
+        self.send_handshake(&mut stream, resolved, handshake_host, final_port, budget)
             .await?;
 
         // Send status request
-        self.send_status_request(&mut stream).await?;
+        self.send_status_request(&mut stream, resolved, budget)
+            .await?;
 
         // Read and parse response
-        let response = self.read_response(&mut stream).await?;
-        let (json, latency) = self.parse_java_response(response, start)?;
+        let response = self.read_packet(&mut stream, resolved, budget).await?;
+        let latency = elapsed_ms(start)?;
+        let json: serde_json::Value =
+            serde_json::from_str(&java::decode_status_response(&response)?)
+                .map_err(McError::JsonError)?;
 
         // Build result
         Ok(ServerStatus {
@@ -101,46 +318,63 @@ impl McClient {
             port: resolved.port(),
             hostname: host.to_string(),
             latency,
+            attempts: 1,
             dns: dns_info,
-            data: ServerData::Java(self.parse_java_json(&json)?),
+            data: ServerData::Java(java::parse_status(&json)),
         })
     }
 
-    pub async fn ping_bedrock(&self, address: &str) -> Result<ServerStatus, McError> {
-        let start = SystemTime::now();
+    async fn ping_bedrock_once(
+        &self,
+        address: &str,
+        budget: &Budget,
+        found: &Mutex<Resolved>,
+    ) -> Result<ServerStatus, McError> {
         let (host, port) = Self::parse_address(address, 19132)?;
-        let resolved = self.resolve_dns(host, port).await?;
-        let dns_info = self.get_dns_info(host).await.ok(); // DNS info is optional
+        let resolved = self.resolve_dns(host, port, budget).await?;
+        let dns_info = self.get_dns_info(host, budget).await.ok(); // DNS info is optional
+        Resolved::record(found, resolved, &dns_info);
+
+        // Waiting for the rate limiter is not part of connecting.
+        let _permit = budget
+            .until_deadline(
+                Phase::Connect,
+                Some(resolved),
+                self.limiter.acquire(resolved.ip()),
+            )
+            .await?;
+        let start = SystemTime::now();
 
-        let socket = UdpSocket::bind("0.0.0.0:0")
+        let local = match resolved {
+            SocketAddr::V4(_) => SocketAddr::from(([0, 0, 0, 0], 0)),
+            SocketAddr::V6(_) => SocketAddr::from(([0u16; 8], 0)),
+        };
+        let socket = self
+            .transport
+            .bind_udp(local)
             .await
             .map_err(McError::IoError)?;
 
         // Send ping packet
-        let ping_packet = self.create_bedrock_ping_packet();
-        timeout(self.timeout, socket.send_to(&ping_packet, resolved))
-            .await
-            .map_err(|_| McError::Timeout)?
-            .map_err(|e| McError::IoError(e))?;
+        let ping_packet = bedrock::UnconnectedPing::new(unix_millis()).encode();
+        budget
+            .limit(
+                Phase::Handshake,
+                Some(resolved),
+                socket.send_to(&ping_packet, resolved),
+            )
+            .await?
+            .map_err(|e| McError::connection(resolved, Phase::Handshake, e))?;
 
         // Receive response
-        let mut buf = [0u8; 1024];
-        let (len, _) = timeout(self.timeout, socket.recv_from(&mut buf))
-            .await
-            .map_err(|_| McError::Timeout)?
-            .map_err(|e| McError::IoError(e))?;
-
-        if len < 35 {
-            return Err(McError::InvalidResponse("Response too short".to_string()));
-        }
-
-        let latency = start
-            .elapsed()
-            .map_err(|_| McError::InvalidResponse("Time error".to_string()))?
-            .as_secs_f64()
-            * 1000.0;
+        let mut buf = vec![0u8; bedrock::MAX_PONG_LEN];
+        let (len, _) = budget
+            .limit(Phase::Read, Some(resolved), socket.recv_from(&mut buf))
+            .await?
+            .map_err(|e| McError::connection(resolved, Phase::Read, e))?;
 
-        let pong_data = String::from_utf8_lossy(&buf[35..len]).to_string();
+        let latency = elapsed_ms(start)?;
+        let pong = bedrock::UnconnectedPong::decode(&buf[..len])?;
 
         Ok(ServerStatus {
             online: true,
@@ -148,32 +382,34 @@ impl McClient {
             port: resolved.port(),
             hostname: host.to_string(),
             latency,
+            attempts: 1,
             dns: dns_info,
-            data: ServerData::Bedrock(self.parse_bedrock_response(&pong_data)?),
+            data: ServerData::Bedrock(bedrock::parse_status(&pong.server_id)?),
         })
     }
 
-    pub async fn ping_many(
-        &self,
-        servers: &[ServerInfo],
-    ) -> Vec<(ServerInfo, Result<ServerStatus, McError>)> {
+    pub async fn ping_many(&self, servers: &[ServerInfo]) -> Vec<PingResult> {
         use futures::stream::StreamExt;
         use tokio::sync::Semaphore;
 
         let semaphore = std::sync::Arc::new(Semaphore::new(self.max_parallel));
         let client = self.clone();
 
-        let futures = servers.iter().map(|server| {
-            let server = server.clone();
-            let semaphore = semaphore.clone();
-            let client = client.clone();
-
-            async move {
-                let _permit = semaphore.acquire().await;
-                let result = client.ping(&server.address, server.edition).await;
-                (server, result)
-            }
-        });
+        // A lazy `map` over `servers` would be held across awaits, and rustc cannot prove
+        // its closure `Send` for every lifetime, which breaks `tokio::spawn(ping_many(..))`.
+        let futures: Vec<_> = servers
+            .iter()
+            .map(|server| {
+                let server = server.clone();
+                let semaphore = semaphore.clone();
+                let client = client.clone();
+
+                async move {
+                    let _permit = semaphore.acquire().await;
+                    client.probe(&server).await
+                }
+            })
+            .collect();
 
         futures::stream::iter(futures)
             .buffer_unordered(self.max_parallel)
@@ -181,127 +417,216 @@ impl McClient {
             .await
     }
 
-    // Helper methods
-    fn parse_address(address: &str, default_port: u16) -> Result<(&str, u16), McError> {
-        if let Some((host, port_str)) = address.split_once(':') {
-            let port = port_str
-                .parse::<u16>()
-                .map_err(|e| McError::InvalidPort(e.to_string()))?;
-            Ok((host, port))
-        } else {
-            Ok((address, default_port))
-        }
-    }
-
-    fn parse_address_with_flag(
-        address: &str,
-        default_port: u16,
-    ) -> Result<(&str, u16, bool), McError> {
-        if let Some((host, port_str)) = address.split_once(':') {
-            let port = port_str
-                .parse::<u16>()
-                .map_err(|e| McError::InvalidPort(e.to_string()))?;
-            Ok((host, port, true)) // explicit port
-        } else {
-            Ok((address, default_port, false)) // no explicit port
-        }
-    }
+    /// Like [`ping_many`](Self::ping_many), saving progress to the `checkpoint` file and
+    /// resuming from it if it exists. Results are returned in the order of `servers`, and
+    /// the checkpoint is deleted once every server has been pinged.
+    pub async fn ping_many_resumable(
+        &self,
+        servers: &[ServerInfo],
+        checkpoint: impl AsRef<Path>,
+    ) -> Result<Vec<PingResult>, McError> {
+        use futures::stream::StreamExt;
 
-    async fn resolve_srv(&self, host: &str, default_port: u16) -> Result<(String, u16), McError> {
-        let cache_key = format!("_minecraft._tcp.{}", host);
+        let targets: Vec<(&str, ServerEdition)> = servers
+            .iter()
+            .map(|s| (s.address.as_str(), s.edition))
+            .collect();
+        let mut progress = Progress::open(
+            checkpoint.as_ref(),
+            &format!("{:?}", targets),
+            servers.len() as u64,
+            None,
+            self.checkpoint_interval,
+        )
+        .await?;
+
+        let pending: Vec<u64> = (0..servers.len() as u64)
+            .filter(|slot| !progress.checkpoint.is_done(*slot))
+            .collect();
+        let mut results = futures::stream::iter(pending)
+            .map(|slot| async move { (slot, self.probe(&servers[slot as usize]).await) })
+            .buffer_unordered(self.max_parallel);
 
-        // Check cache with TTL validation
-        if let Some(entry) = SRV_CACHE.get(&cache_key) {
-            let (srv_host, srv_port, timestamp) = entry.value().clone();
-            if timestamp
-                .elapsed()
-                .map(|d| d.as_secs() < DNS_CACHE_TTL)
-                .unwrap_or(false)
-            {
-                return Ok((srv_host, srv_port));
-            }
+        while let Some((slot, result)) = results.next().await {
+            progress.record(slot, Some(result)).await?;
         }
 
-        // Try SRV lookup
-        match self.lookup_srv(host).await {
-            Ok((srv_host, srv_port)) => {
-                SRV_CACHE.insert(cache_key, (srv_host.clone(), srv_port, SystemTime::now()));
-                Ok((srv_host, srv_port))
-            }
-            Err(_) => {
-                // No SRV record found, use default
-                Ok((host.to_string(), default_port))
-            }
-        }
+        let mut checkpoint = progress.finish().await?;
+        checkpoint.results.sort_by_key(|r| r.slot);
+        Ok(checkpoint.results.into_iter().map(|r| r.result).collect())
     }
 
-    async fn lookup_srv(&self, host: &str) -> Result<(String, u16), McError> {
-        let resolver =
-            TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default());
+    /// Pings every target of `scan` and streams the servers that answered, within this
+    /// client's concurrency and rate limits.
+    pub fn scan<'a>(
+        &'a self,
+        scan: &'a Scan,
+    ) -> Result<impl futures::Stream<Item = PingResult> + 'a, McError> {
+        use futures::stream::StreamExt;
 
-        let srv_name = format!("_minecraft._tcp.{}", host);
+        let targets = scan.targets()?;
+        Ok(futures::stream::iter(targets)
+            .map(move |target| async move { self.probe(&target).await })
+            .buffer_unordered(self.max_parallel)
+            .filter(|result| futures::future::ready(result.is_online())))
+    }
 
-        let response = timeout(self.timeout, resolver.srv_lookup(&srv_name))
-            .await
-            .map_err(|_| McError::Timeout)?
-            .map_err(|e| McError::DnsError(format!("SRV lookup failed: {}", e)))?;
+    /// Like [`scan`](Self::scan), saving progress to the `checkpoint` file and resuming
+    /// from it if it exists, in the target order the checkpoint was started with. Hits
+    /// found before the restart are yielded first. The checkpoint is deleted when the scan
+    /// completes.
+    pub async fn scan_resumable<'a, P: AsRef<Path>>(
+        &'a self,
+        scan: &Scan,
+        checkpoint: P,
+    ) -> Result<impl futures::Stream<Item = Result<PingResult, McError>> + use<'a, P>, McError>
+    {
+        use futures::stream::StreamExt;
 
-        // Get the first SRV record with the highest priority (lowest number)
-        let srv = response
+        let progress = Progress::open(
+            checkpoint.as_ref(),
+            &scan.fingerprint(),
+            scan.len()?,
+            Some(scan.order()),
+            self.checkpoint_interval,
+        )
+        .await?;
+        let order = progress.checkpoint.order.unwrap_or(scan.order());
+
+        let mut targets = scan.clone().with_order(order).targets()?;
+        targets.skip_to(progress.checkpoint.done_before);
+        let done_after = progress.checkpoint.done_after.clone();
+        let saved: Vec<Result<PingResult, McError>> = progress
+            .checkpoint
+            .results
             .iter()
-            .min_by_key(|r| r.priority())
-            .ok_or_else(|| McError::DnsError("No SRV records found".to_string()))?;
+            .map(|r| Ok(r.result.clone()))
+            .collect();
 
-        let target = srv.target().to_utf8();
-        let port = srv.port();
+        let pings = futures::stream::iter(
+            std::iter::from_fn(move || targets.next_indexed())
+                .filter(move |(slot, _)| !done_after.contains(slot)),
+        )
+        .map(move |(slot, target)| async move { (slot, self.probe(&target).await) })
+        .buffer_unordered(self.max_parallel);
+
+        let hits = futures::stream::unfold(
+            (Box::pin(pings), Some(progress)),
+            |(mut pings, mut progress)| async move {
+                loop {
+                    let current = progress.as_mut()?;
+                    let Some((slot, result)) = pings.next().await else {
+                        return match progress.take()?.finish().await {
+                            Ok(_) => None,
+                            Err(e) => Some((Err(e), (pings, None))),
+                        };
+                    };
+
+                    let hit = result.is_online().then_some(result);
+                    if let Err(e) = current.record(slot, hit.clone()).await {
+                        return Some((Err(e), (pings, None)));
+                    }
+                    if let Some(hit) = hit {
+                        return Some((Ok(hit), (pings, progress)));
+                    }
+                }
+            },
+        );
 
-        // Remove trailing dot from target if present
-        let target = target.trim_end_matches('.');
+        Ok(futures::stream::iter(saved).chain(hits))
+    }
 
-        Ok((target.to_string(), port))
+    /// Finds Bedrock games on the local network by broadcasting an unconnected ping, the
+    /// way the game's Friends tab does. Always uses real sockets, whatever the transport.
+    pub async fn discover_bedrock(
+        &self,
+        discovery: &BedrockDiscovery,
+    ) -> Result<Vec<BedrockLanServer>, McError> {
+        lan::bedrock::discover(discovery).await
     }
 
-    async fn resolve_dns(&self, host: &str, port: u16) -> Result<SocketAddr, McError> {
-        let cache_key = format!("{}:{}", host, port);
+    // Helper methods
+    fn parse_address(address: &str, default_port: u16) -> Result<(&str, u16), McError> {
+        Self::parse_address_with_flag(address, default_port).map(|(host, port, _)| (host, port))
+    }
 
-        // Check cache with TTL validation
-        if let Some(entry) = DNS_CACHE.get(&cache_key) {
-            let (addr, timestamp) = *entry.value();
-            if timestamp
-                .elapsed()
-                .map(|d| d.as_secs() < DNS_CACHE_TTL)
-                .unwrap_or(false)
-            {
-                return Ok(addr);
-            }
+    pub(crate) fn parse_address_with_flag(
+        address: &str,
+        default_port: u16,
+    ) -> Result<(&str, u16, bool), McError> {
+        // IPv6 literals are bracketed when a port follows, as in `[::1]:25565`
+        if let Some(rest) = address.strip_prefix('[') {
+            let (host, tail) = rest
+                .split_once(']')
+                .ok_or_else(|| McError::InvalidAddress(address.to_string()))?;
+            return match tail.strip_prefix(':') {
+                Some(port_str) => Ok((host, parse_port(port_str)?, true)),
+                None if tail.is_empty() => Ok((host, default_port, false)),
+                None => Err(McError::InvalidAddress(address.to_string())),
+            };
+        }
+        if address.parse::<std::net::Ipv6Addr>().is_ok() {
+            return Ok((address, default_port, false));
         }
 
-        // Resolve and cache
-        let addrs: Vec<SocketAddr> = format!("{}:{}", host, port)
-            .to_socket_addrs()
-            .map_err(|e| McError::DnsError(e.to_string()))?
-            .collect();
+        if let Some((host, port_str)) = address.split_once(':') {
+            Ok((host, parse_port(port_str)?, true)) // explicit port
+        } else {
+            Ok((address, default_port, false)) // no explicit port
+        }
+    }
+
+    async fn resolve_srv(
+        &self,
+        host: &str,
+        default_port: u16,
+        budget: &Budget,
+    ) -> Result<(String, u16), McError> {
+        let record = budget
+            .limit(Phase::Resolve, None, self.transport.lookup_srv(host))
+            .await??;
+
+        // No SRV record found, use default
+        Ok(record.unwrap_or_else(|| (host.to_string(), default_port)))
+    }
+
+    async fn resolve_dns(
+        &self,
+        host: &str,
+        port: u16,
+        budget: &Budget,
+    ) -> Result<SocketAddr, McError> {
+        let addrs = self.lookup_ips(host, budget).await?;
 
-        let addr = addrs
+        let ip = addrs
             .iter()
             .find(|a| a.is_ipv4())
             .or_else(|| addrs.first())
             .copied()
-            .ok_or_else(|| McError::DnsError("No addresses resolved".to_string()))?;
+            .ok_or_else(|| McError::DnsError {
+                host: host.to_string(),
+                kind: DnsErrorKind::NoRecords,
+                message: "No addresses resolved".to_string(),
+            })?;
 
-        DNS_CACHE.insert(cache_key, (addr, SystemTime::now()));
-        Ok(addr)
+        Ok(SocketAddr::new(ip, port))
     }
 
-    async fn get_dns_info(&self, host: &str) -> Result<DnsInfo, McError> {
-        // Simple implementation - in production you might want to use a proper DNS library
-        let addrs: Vec<SocketAddr> = format!("{}:0", host)
-            .to_socket_addrs()
-            .map_err(|e| McError::DnsError(e.to_string()))?
-            .collect();
+    async fn lookup_ips(&self, host: &str, budget: &Budget) -> Result<Vec<IpAddr>, McError> {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+
+        budget
+            .limit(Phase::Resolve, None, self.transport.resolve(host))
+            .await?
+    }
+
+    async fn get_dns_info(&self, host: &str, budget: &Budget) -> Result<DnsInfo, McError> {
+        let addrs = self.lookup_ips(host, budget).await?;
 
         Ok(DnsInfo {
-            a_records: addrs.iter().map(|a| a.ip().to_string()).collect(),
+            a_records: addrs.iter().map(|a| a.to_string()).collect(),
             cname: None, // This would require proper DNS queries
             ttl: 300,
         })
@@ -309,305 +634,89 @@ impl McClient {
 
     async fn send_handshake(
         &self,
-        stream: &mut TcpStream,
+        stream: &mut Box<dyn Stream>,
+        addr: SocketAddr,
         host: &str,
         port: u16,
+        budget: &Budget,
     ) -> Result<(), McError> {
-        let mut handshake = Vec::with_capacity(64);
-        write_var_int(&mut handshake, 0x00);
-        write_var_int(&mut handshake, 47);
-        write_string(&mut handshake, host);
-        handshake.extend_from_slice(&port.to_be_bytes());
-        write_var_int(&mut handshake, 1);
-
-        let mut packet = Vec::with_capacity(handshake.len() + 5);
-        write_var_int(&mut packet, handshake.len() as i32);
-        packet.extend_from_slice(&handshake);
-
-        timeout(self.timeout, stream.write_all(&packet))
-            .await
-            .map_err(|_| McError::Timeout)?
-            .map_err(McError::IoError)
-    }
+        let packet = java::Handshake::status(host, port).encode();
 
-    async fn send_status_request(&self, stream: &mut TcpStream) -> Result<(), McError> {
-        let mut status_request = Vec::with_capacity(5);
-        write_var_int(&mut status_request, 0x00);
+        budget
+            .limit(Phase::Handshake, Some(addr), stream.write_all(&packet))
+            .await?
+            .map_err(|e| McError::connection(addr, Phase::Handshake, e))
+    }
 
-        let mut status_packet = Vec::with_capacity(status_request.len() + 5);
-        write_var_int(&mut status_packet, status_request.len() as i32);
-        status_packet.extend_from_slice(&status_request);
+    async fn send_status_request(
+        &self,
+        stream: &mut Box<dyn Stream>,
+        addr: SocketAddr,
+        budget: &Budget,
+    ) -> Result<(), McError> {
+        let status_packet = java::encode_status_request();
 
-        timeout(self.timeout, stream.write_all(&status_packet))
-            .await
-            .map_err(|_| McError::Timeout)?
-            .map_err(McError::IoError)
+        budget
+            .limit(
+                Phase::Handshake,
+                Some(addr),
+                stream.write_all(&status_packet),
+            )
+            .await?
+            .map_err(|e| McError::connection(addr, Phase::Handshake, e))
     }
 
-    async fn read_response(&self, stream: &mut TcpStream) -> Result<Vec<u8>, McError> {
-        let mut response = Vec::with_capacity(1024);
+    async fn read_packet(
+        &self,
+        stream: &mut Box<dyn Stream>,
+        addr: SocketAddr,
+        budget: &Budget,
+    ) -> Result<RawPacket, McError> {
+        let mut decoder = FrameDecoder::new(self.max_response_size);
         let mut buf = [0u8; 4096];
-        let mut expected_length = None;
 
         loop {
-            let n = timeout(self.timeout, stream.read(&mut buf))
-                .await
-                .map_err(|_| McError::Timeout)?
-                .map_err(McError::IoError)?;
-
-            if n == 0 {
-                break;
+            if let Some(packet) = decoder.next_packet()? {
+                return Ok(packet);
             }
 
-            response.extend_from_slice(&buf[..n]);
-
-            // Check if we have enough data to determine packet length
-            if expected_length.is_none() && response.len() >= 5 {
-                let mut cursor = Cursor::new(&response);
-                if let Ok(packet_length) = read_var_int(&mut cursor) {
-                    expected_length = Some(cursor.position() as usize + packet_length as usize);
-                }
-            }
+            let n = budget
+                .limit(Phase::Read, Some(addr), stream.read(&mut buf))
+                .await?
+                .map_err(|e| McError::connection(addr, Phase::Read, e))?;
 
-            if let Some(expected) = expected_length {
-                if response.len() >= expected {
-                    break;
-                }
+            if n == 0 {
+                let message = if decoder.buffered() == 0 {
+                    "connection closed before a response"
+                } else {
+                    "connection closed mid-packet"
+                };
+                let eof = io::Error::new(io::ErrorKind::UnexpectedEof, message);
+                return Err(McError::connection(addr, Phase::Read, eof));
             }
-        }
-
-        if response.is_empty() {
-            return Err(McError::InvalidResponse(
-                "No response from server".to_string(),
-            ));
-        }
-
-        Ok(response)
-    }
-
-    fn parse_java_response(
-        &self,
-        response: Vec<u8>,
-        start: SystemTime,
-    ) -> Result<(serde_json::Value, f64), McError> {
-        let mut cursor = Cursor::new(&response);
-        let packet_length = read_var_int(&mut cursor).map_err(|e| {
-            McError::InvalidResponse(format!("Failed to read packet length: {}", e))
-        })?;
-
-        let total_expected = cursor.position() as usize + packet_length as usize;
-        if response.len() < total_expected {
-            return Err(McError::InvalidResponse(format!(
-                "Incomplete packet: expected {}, got {}",
-                total_expected,
-                response.len()
-            )));
-        }
-
-        let packet_id = read_var_int(&mut cursor)
-            .map_err(|e| McError::InvalidResponse(format!("Failed to read packet ID: {}", e)))?;
-
-        if packet_id != 0x00 {
-            return Err(McError::InvalidResponse(format!(
-                "Unexpected packet ID: {}",
-                packet_id
-            )));
-        }
-
-        let json_length = read_var_int(&mut cursor)
-            .map_err(|e| McError::InvalidResponse(format!("Failed to read JSON length: {}", e)))?;
-
-        if cursor.position() as usize + json_length as usize > response.len() {
-            return Err(McError::InvalidResponse("JSON data truncated".to_string()));
-        }
-
-        let json_buf = &response
-            [cursor.position() as usize..cursor.position() as usize + json_length as usize];
-        let json_str = String::from_utf8(json_buf.to_vec()).map_err(McError::Utf8Error)?;
-
-        let json: serde_json::Value =
-            serde_json::from_str(&json_str).map_err(McError::JsonError)?;
-
-        let latency = start
-            .elapsed()
-            .map_err(|_| McError::InvalidResponse("Time error".to_string()))?
-            .as_secs_f64()
-            * 1000.0;
-
-        Ok((json, latency))
-    }
-
-    fn parse_java_json(&self, json: &serde_json::Value) -> Result<JavaStatus, McError> {
-        let version = JavaVersion {
-            name: json["version"]["name"]
-                .as_str()
-                .unwrap_or("Unknown")
-                .to_string(),
-            protocol: json["version"]["protocol"].as_i64().unwrap_or(0),
-        };
 
-        let players = JavaPlayers {
-            online: json["players"]["online"].as_i64().unwrap_or(0),
-            max: json["players"]["max"].as_i64().unwrap_or(0),
-            sample: if let Some(sample) = json["players"]["sample"].as_array() {
-                Some(
-                    sample
-                        .iter()
-                        .filter_map(|p| {
-                            Some(JavaPlayer {
-                                name: p["name"].as_str()?.to_string(),
-                                id: p["id"].as_str()?.to_string(),
-                            })
-                        })
-                        .collect(),
-                )
-            } else {
-                None
-            },
-        };
-
-        let description = if let Some(desc) = json["description"].as_str() {
-            desc.to_string()
-        } else if let Some(text) = json["description"]["text"].as_str() {
-            text.to_string()
-        } else {
-            "No description".to_string()
-        };
-
-        let favicon = json["favicon"].as_str().map(|s| s.to_string());
-        let map = json["map"].as_str().map(|s| s.to_string());
-        let gamemode = json["gamemode"].as_str().map(|s| s.to_string());
-        let software = json["software"].as_str().map(|s| s.to_string());
-
-        let plugins = if let Some(plugins_array) = json["plugins"].as_array() {
-            Some(
-                plugins_array
-                    .iter()
-                    .filter_map(|p| {
-                        Some(JavaPlugin {
-                            name: p["name"].as_str()?.to_string(),
-                            version: p["version"].as_str().map(|s| s.to_string()),
-                        })
-                    })
-                    .collect(),
-            )
-        } else {
-            None
-        };
-
-        let mods = if let Some(mods_array) = json["mods"].as_array() {
-            Some(
-                mods_array
-                    .iter()
-                    .filter_map(|m| {
-                        Some(JavaMod {
-                            modid: m["modid"].as_str()?.to_string(),
-                            version: m["version"].as_str().map(|s| s.to_string()),
-                        })
-                    })
-                    .collect(),
-            )
-        } else {
-            None
-        };
-
-        Ok(JavaStatus {
-            version,
-            players,
-            description,
-            favicon,
-            map,
-            gamemode,
-            software,
-            plugins,
-            mods,
-            raw_data: json.clone(),
-        })
-    }
-
-    fn create_bedrock_ping_packet(&self) -> Vec<u8> {
-        let mut ping_packet = Vec::with_capacity(35);
-        ping_packet.push(0x01);
-        ping_packet.extend_from_slice(
-            &(SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64)
-                .to_be_bytes(),
-        );
-        ping_packet.extend_from_slice(&[
-            0x00, 0xFF, 0xFF, 0x00, 0xFE, 0xFE, 0xFE, 0xFE, 0xFD, 0xFD, 0xFD, 0xFD, 0x12, 0x34,
-            0x56, 0x78,
-        ]);
-        ping_packet.extend_from_slice(&[0x00; 8]);
-        ping_packet
-    }
-
-    fn parse_bedrock_response(&self, pong_data: &str) -> Result<BedrockStatus, McError> {
-        let parts: Vec<&str> = pong_data.split(';').collect();
-
-        if parts.len() < 6 {
-            return Err(McError::InvalidResponse(
-                "Invalid Bedrock response".to_string(),
-            ));
+            decoder.push(&buf[..n]);
         }
-
-        Ok(BedrockStatus {
-            edition: parts[0].to_string(),
-            motd: parts[1].to_string(),
-            protocol_version: parts[2].to_string(),
-            version: parts[3].to_string(),
-            online_players: parts[4].to_string(),
-            max_players: parts[5].to_string(),
-            server_uid: parts.get(6).map_or("", |s| *s).to_string(),
-            motd2: parts.get(7).map_or("", |s| *s).to_string(),
-            game_mode: parts.get(8).map_or("", |s| *s).to_string(),
-            game_mode_numeric: parts.get(9).map_or("", |s| *s).to_string(),
-            port_ipv4: parts.get(10).map_or("", |s| *s).to_string(),
-            port_ipv6: parts.get(11).map_or("", |s| *s).to_string(),
-            map: parts.get(12).map(|s| s.to_string()),
-            software: parts.get(13).map(|s| s.to_string()),
-            raw_data: pong_data.to_string(),
-        })
     }
 }
 
 // Helper functions
-fn write_var_int(buffer: &mut Vec<u8>, value: i32) {
-    let mut value = value as u32;
-    loop {
-        let mut temp = (value & 0x7F) as u8;
-        value >>= 7;
-        if value != 0 {
-            temp |= 0x80;
-        }
-        buffer.push(temp);
-        if value == 0 {
-            break;
-        }
-    }
+fn parse_port(port: &str) -> Result<u16, McError> {
+    port.parse::<u16>()
+        .map_err(|e| McError::InvalidPort(e.to_string()))
 }
 
-fn write_string(buffer: &mut Vec<u8>, s: &str) {
-    write_var_int(buffer, s.len() as i32);
-    buffer.extend_from_slice(s.as_bytes());
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
 
-fn read_var_int(reader: &mut impl std::io::Read) -> Result<i32, String> {
-    let mut result = 0i32;
-    let mut shift = 0;
-    loop {
-        let mut byte = [0u8];
-        reader.read_exact(&mut byte).map_err(|e| e.to_string())?;
-        let value = byte[0] as i32;
-        result |= (value & 0x7F) << shift;
-        shift += 7;
-        if shift > 35 {
-            return Err("VarInt too big".to_string());
-        }
-        if (value & 0x80) == 0 {
-            break;
-        }
-    }
-    Ok(result)
+fn elapsed_ms(start: SystemTime) -> Result<f64, McError> {
+    Ok(start
+        .elapsed()
+        .map_err(|_| McError::InvalidResponse("Time error".to_string()))?
+        .as_secs_f64()
+        * 1000.0)
 }
@@ -0,0 +1,599 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Declarative server lists in TOML, JSON or YAML.
+//!
+//! ```toml
+//! [defaults]
+//! edition = "java"
+//! timeout = "5s"
+//! interval = "1m"
+//!
+//! [groups.lobbies]
+//! interval = "15s"
+//! tags = ["public"]
+//!
+//! [[servers]]
+//! name = "Lobby 1"
+//! address = "10.0.0.11:25565"
+//! group = "lobbies"
+//! virtual_host = "play.example.com"
+//! expected_version = "1.21.4"
+//! ```
+//!
+//! A server's own settings win over its group's, which win over `defaults`; tags from all
+//! three are merged. Servers without an edition anywhere are Java. Durations are seconds
+//! or strings such as `"500ms"`, `"30s"`, `"5m"` and `"1h"`. JSON and YAML files have the
+//! same shape; TOML needs the `toml` feature and YAML the `yaml` feature.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::de::{self, Deserializer, Visitor};
+
+use crate::client::McClient;
+use crate::error::McError;
+use crate::models::{ServerData, ServerEdition, ServerInfo};
+use crate::monitor::MonitorTarget;
+use crate::timeouts::Timeouts;
+
+/// A file format for [`ServerList`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// The format a file extension names: `.toml`, `.json`, `.yaml` or `.yml`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "json" => Some(ConfigFormat::Json),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Where and why a server list failed to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub path: Option<PathBuf>,
+    /// 1-based, when the position is known.
+    pub line: Option<usize>,
+    /// 1-based, in characters.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{line}:{column}"),
+            (Some(line), None) => line.to_string(),
+            _ => String::new(),
+        };
+        match (&self.path, position.is_empty()) {
+            (Some(path), true) => write!(f, "{}: {}", path.display(), self.message),
+            (Some(path), false) => write!(f, "{}:{position}: {}", path.display(), self.message),
+            (None, false) => write!(f, "line {position}: {}", self.message),
+            (None, true) => f.write_str(&self.message),
+        }
+    }
+}
+
+/// One server of a [`ServerList`], with its group's and the defaults' settings applied.
+#[derive(Debug, Clone)]
+pub struct ServerEntry {
+    /// The display name, or the address if none was given.
+    pub name: String,
+    /// Address, edition, timeouts and virtual host, ready for `probe` and `ping_many`.
+    pub server: ServerInfo,
+    pub group: Option<String>,
+    pub tags: Vec<String>,
+    /// How often a monitor should poll the server.
+    pub interval: Option<Duration>,
+    /// Text the reported version name should contain, e.g. `1.21.4`.
+    pub expected_version: Option<String>,
+}
+
+impl ServerEntry {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// A monitor target with this entry's interval.
+    pub fn monitor_target(&self) -> MonitorTarget {
+        let target = MonitorTarget::new(self.server.clone());
+        match self.interval {
+            Some(interval) => target.with_interval(interval),
+            None => target,
+        }
+    }
+
+    /// Whether a server reports the expected version; always true if none is expected.
+    pub fn version_matches(&self, data: &ServerData) -> bool {
+        let Some(expected) = &self.expected_version else {
+            return true;
+        };
+        let version = match data {
+            ServerData::Java(java) => &java.version.name,
+            ServerData::Bedrock(bedrock) => &bedrock.version,
+        };
+        version.contains(expected.as_str())
+    }
+}
+
+/// A list of servers loaded from a config file.
+#[derive(Debug, Clone, Default)]
+pub struct ServerList {
+    pub servers: Vec<ServerEntry>,
+}
+
+impl ServerList {
+    /// Reads a file, choosing the format by its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, McError> {
+        let path = path.as_ref();
+        let with_path = |error: McError| match error {
+            McError::Config(mut error) => {
+                error.path = Some(path.to_path_buf());
+                McError::Config(error)
+            }
+            error => error,
+        };
+
+        let format = ConfigFormat::from_path(path).ok_or_else(|| {
+            with_path(config_error(
+                None,
+                "unknown file extension; expected .toml, .json, .yaml or .yml",
+            ))
+        })?;
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, format).map_err(with_path)
+    }
+
+    pub fn parse(text: &str, format: ConfigFormat) -> Result<Self, McError> {
+        let raw: RawList = match format {
+            ConfigFormat::Json => serde_json::from_str(text).map_err(|error| {
+                let position = (error.line() > 0).then(|| (error.line(), error.column()));
+                config_error(position, strip_position(&error.to_string()))
+            })?,
+            #[cfg(feature = "toml")]
+            ConfigFormat::Toml => toml::from_str(text).map_err(|error| {
+                let position = error.span().map(|span| offset_position(text, span.start));
+                config_error(position, error.message())
+            })?,
+            #[cfg(not(feature = "toml"))]
+            ConfigFormat::Toml => {
+                return Err(config_error(None, "TOML needs the `toml` feature"));
+            }
+            #[cfg(feature = "yaml")]
+            ConfigFormat::Yaml => serde_yaml::from_str(text).map_err(|error| {
+                let position = error
+                    .location()
+                    .map(|location| (location.line(), location.column()));
+                config_error(position, strip_position(&error.to_string()))
+            })?,
+            #[cfg(not(feature = "yaml"))]
+            ConfigFormat::Yaml => {
+                return Err(config_error(None, "YAML needs the `yaml` feature"));
+            }
+        };
+        raw.resolve(text)
+    }
+
+    /// Every server, ready for `ping_many`.
+    pub fn server_infos(&self) -> Vec<ServerInfo> {
+        self.servers
+            .iter()
+            .map(|entry| entry.server.clone())
+            .collect()
+    }
+
+    /// Every server as a monitor target with its interval.
+    pub fn monitor_targets(&self) -> Vec<MonitorTarget> {
+        self.servers
+            .iter()
+            .map(ServerEntry::monitor_target)
+            .collect()
+    }
+
+    pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a ServerEntry> {
+        self.servers.iter().filter(move |entry| entry.has_tag(tag))
+    }
+
+    pub fn in_group<'a>(&'a self, group: &'a str) -> impl Iterator<Item = &'a ServerEntry> {
+        self.servers
+            .iter()
+            .filter(move |entry| entry.group.as_deref() == Some(group))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawList {
+    #[serde(default)]
+    defaults: RawSettings,
+    #[serde(default)]
+    groups: BTreeMap<String, RawSettings>,
+    #[serde(default)]
+    servers: Vec<RawServer>,
+}
+
+/// Settings a group or the defaults pass on to servers.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSettings {
+    edition: Option<Edition>,
+    timeout: Option<Seconds>,
+    interval: Option<Seconds>,
+    expected_version: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawServer {
+    address: Address,
+    name: Option<String>,
+    edition: Option<Edition>,
+    group: Option<String>,
+    timeout: Option<Seconds>,
+    interval: Option<Seconds>,
+    virtual_host: Option<String>,
+    expected_version: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl RawList {
+    fn resolve(self, text: &str) -> Result<ServerList, McError> {
+        let no_group = RawSettings::default();
+        let mut servers = Vec::with_capacity(self.servers.len());
+        for (index, raw) in self.servers.into_iter().enumerate() {
+            let group = match &raw.group {
+                Some(name) => self.groups.get(name).ok_or_else(|| {
+                    config_error(
+                        locate(text, "group", name),
+                        format!("servers[{index}]: unknown group `{name}`"),
+                    )
+                })?,
+                None => &no_group,
+            };
+            let defaults = &self.defaults;
+
+            let edition = raw
+                .edition
+                .or(group.edition)
+                .or(defaults.edition)
+                .map_or(ServerEdition::Java, |edition| edition.0);
+            let mut server = ServerInfo::new(raw.address.0.clone(), edition);
+            if let Some(timeout) = raw.timeout.or(group.timeout).or(defaults.timeout) {
+                server = server.with_timeouts(per_operation(timeout.0));
+            }
+            if let Some(host) = raw.virtual_host {
+                server = server.with_virtual_host(host);
+            }
+
+            let mut tags: Vec<String> = Vec::new();
+            for tag in raw.tags.iter().chain(&group.tags).chain(&defaults.tags) {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+
+            servers.push(ServerEntry {
+                name: raw.name.unwrap_or(raw.address.0),
+                server,
+                group: raw.group,
+                tags,
+                interval: raw
+                    .interval
+                    .or(group.interval)
+                    .or(defaults.interval)
+                    .map(|interval| interval.0),
+                expected_version: raw
+                    .expected_version
+                    .or_else(|| group.expected_version.clone())
+                    .or_else(|| defaults.expected_version.clone()),
+            });
+        }
+        Ok(ServerList { servers })
+    }
+}
+
+/// An edition name, checked while parsing so the error carries its position.
+#[derive(Clone, Copy)]
+struct Edition(ServerEdition);
+
+impl<'de> Deserialize<'de> for Edition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map(Edition).map_err(|_| {
+            de::Error::custom(format!(
+                "unknown edition `{name}`, expected `java` or `bedrock`"
+            ))
+        })
+    }
+}
+
+/// A `host[:port]` the client can parse.
+struct Address(String);
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        if address.trim().is_empty() {
+            return Err(de::Error::custom("address is empty"));
+        }
+        McClient::parse_address_with_flag(&address, 25565)
+            .map_err(|error| de::Error::custom(format!("invalid address `{address}`: {error}")))?;
+        Ok(Address(address))
+    }
+}
+
+/// A positive duration, as seconds or a string with a unit.
+#[derive(Clone, Copy)]
+struct Seconds(Duration);
+
+impl<'de> Deserialize<'de> for Seconds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SecondsVisitor;
+
+        impl Visitor<'_> for SecondsVisitor {
+            type Value = Seconds;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a duration such as 30 or \"30s\"")
+            }
+
+            fn visit_u64<E: de::Error>(self, seconds: u64) -> Result<Seconds, E> {
+                self.visit_f64(seconds as f64)
+            }
+
+            fn visit_i64<E: de::Error>(self, seconds: i64) -> Result<Seconds, E> {
+                self.visit_f64(seconds as f64)
+            }
+
+            fn visit_f64<E: de::Error>(self, seconds: f64) -> Result<Seconds, E> {
+                if seconds.is_nan() || seconds <= 0.0 {
+                    return Err(E::custom(format!(
+                        "duration must be positive, got {seconds}"
+                    )));
+                }
+                Duration::try_from_secs_f64(seconds)
+                    .map(Seconds)
+                    .map_err(|_| E::custom(format!("duration of {seconds}s is too long")))
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Seconds, E> {
+                let split = text
+                    .find(|c: char| !c.is_ascii_digit() && c != '.')
+                    .unwrap_or(text.len());
+                let (number, unit) = text.split_at(split);
+                let scale = match unit.trim() {
+                    "ms" => 0.001,
+                    "" | "s" => 1.0,
+                    "m" => 60.0,
+                    "h" => 3600.0,
+                    _ => {
+                        return Err(E::custom(format!(
+                            "invalid duration `{text}`, expected a unit of ms, s, m or h"
+                        )));
+                    }
+                };
+                let number: f64 = number
+                    .parse()
+                    .map_err(|_| E::custom(format!("invalid duration `{text}`")))?;
+                self.visit_f64(number * scale)
+            }
+        }
+
+        deserializer.deserialize_any(SecondsVisitor)
+    }
+}
+
+/// Default timeouts with `timeout` for each operation, keeping the overall deadline unless
+/// a single operation may take longer.
+fn per_operation(timeout: Duration) -> Timeouts {
+    let defaults = Timeouts::default();
+    Timeouts {
+        total: defaults.total.map(|total| total.max(timeout)),
+        ..defaults
+    }
+    .with_dns(timeout)
+    .with_connect(timeout)
+    .with_read(timeout)
+}
+
+fn config_error(position: Option<(usize, usize)>, message: impl Into<String>) -> McError {
+    McError::Config(ConfigError {
+        path: None,
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+        message: message.into(),
+    })
+}
+
+/// The 1-based line and column of a byte offset.
+#[cfg(feature = "toml")]
+fn offset_position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Best-effort position of `value` on a line that also mentions `key`, for errors found
+/// only after the whole file was parsed.
+fn locate(text: &str, key: &str, value: &str) -> Option<(usize, usize)> {
+    let boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric() && c != '_' && c != '-');
+    text.lines().enumerate().find_map(|(number, line)| {
+        if !line.contains(key) {
+            return None;
+        }
+        line.match_indices(value).find_map(|(start, _)| {
+            let before = line[..start].chars().next_back();
+            let after = line[start + value.len()..].chars().next();
+            (boundary(before) && boundary(after))
+                .then(|| (number + 1, line[..start].chars().count() + 1))
+        })
+    })
+}
+
+/// Drops the ` at line N column M` suffix parsers add, since the position is kept apart.
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(index) if message[index..].contains(" column ") => message[..index].to_string(),
+        _ => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_err(text: &str, format: ConfigFormat) -> ConfigError {
+        match ServerList::parse(text, format) {
+            Err(McError::Config(error)) => error,
+            Err(other) => panic!("expected a config error, got {other}"),
+            Ok(_) => panic!("expected a config error"),
+        }
+    }
+
+    #[test]
+    fn server_settings_override_group_and_defaults() {
+        let text = r#"{
+            "defaults": {
+                "edition": "bedrock",
+                "timeout": 5,
+                "interval": "1m",
+                "expected_version": "1.20",
+                "tags": ["all"]
+            },
+            "groups": {
+                "eu": { "timeout": "2s", "expected_version": "1.21", "tags": ["eu"] }
+            },
+            "servers": [
+                { "address": "eu.example.com", "group": "eu", "interval": 30, "tags": ["lobby", "eu"] },
+                { "address": "play.example.com:25570", "name": "Play", "edition": "java" }
+            ]
+        }"#;
+        let list = ServerList::parse(text, ConfigFormat::Json).unwrap();
+
+        let eu = &list.servers[0];
+        assert_eq!(eu.name, "eu.example.com");
+        assert_eq!(eu.server.edition, ServerEdition::Bedrock);
+        assert_eq!(
+            eu.server.timeouts,
+            Some(per_operation(Duration::from_secs(2)))
+        );
+        assert_eq!(eu.interval, Some(Duration::from_secs(30)));
+        assert_eq!(eu.expected_version.as_deref(), Some("1.21"));
+        assert_eq!(eu.tags, ["lobby", "eu", "all"]);
+
+        let play = &list.servers[1];
+        assert_eq!(play.name, "Play");
+        assert_eq!(play.server.address, "play.example.com:25570");
+        assert_eq!(play.server.edition, ServerEdition::Java);
+        assert_eq!(
+            play.server.timeouts,
+            Some(per_operation(Duration::from_secs(5)))
+        );
+        assert_eq!(play.interval, Some(Duration::from_secs(60)));
+        assert_eq!(play.expected_version.as_deref(), Some("1.20"));
+        assert_eq!(play.tags, ["all"]);
+
+        assert_eq!(list.in_group("eu").count(), 1);
+        assert_eq!(list.tagged("all").count(), 2);
+    }
+
+    #[test]
+    fn timeouts_keep_the_overall_deadline() {
+        let text = r#"{"servers": [
+            {"address": "a.example.com", "timeout": 5},
+            {"address": "b.example.com", "timeout": "2m"}
+        ]}"#;
+        let list = ServerList::parse(text, ConfigFormat::Json).unwrap();
+
+        let short = list.servers[0].server.timeouts.unwrap();
+        assert_eq!(short.read, Duration::from_secs(5));
+        assert_eq!(short.total, Timeouts::default().total);
+        let long = list.servers[1].server.timeouts.unwrap();
+        assert_eq!(long.connect, Duration::from_secs(120));
+        assert_eq!(long.total, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn rejects_out_of_range_durations() {
+        for timeout in ["-1", "0", "1e300", "\"99999999999999999999h\""] {
+            let text =
+                format!(r#"{{"servers": [{{"address": "a.example.com", "timeout": {timeout}}}]}}"#);
+            let error = config_err(&text, ConfigFormat::Json);
+            assert!(
+                error.message.contains("duration"),
+                "{timeout}: {}",
+                error.message
+            );
+        }
+    }
+
+    #[test]
+    fn edition_defaults_to_java_without_timeouts() {
+        let list = ServerList::parse(
+            r#"{"servers": [{"address": "a.example.com"}]}"#,
+            ConfigFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(list.servers[0].server.edition, ServerEdition::Java);
+        assert!(list.servers[0].server.timeouts.is_none());
+        assert!(list.servers[0].interval.is_none());
+    }
+
+    #[test]
+    fn json_errors_report_line_and_column() {
+        let text = "{\n  \"servers\": [\n    {\"address\": \"a.example.com\", \"port\": 1}\n  ]\n}";
+        let error = config_err(text, ConfigFormat::Json);
+        // serde_json points at the end of the offending key.
+        assert_eq!((error.line, error.column), (Some(3), Some(39)));
+        assert!(error.message.contains("unknown field `port`"));
+        assert!(!error.message.contains(" at line "));
+    }
+
+    #[test]
+    fn unknown_group_is_located() {
+        let text = "{\n  \"groups\": {\"eu\": {}},\n  \"servers\": [\n    {\"address\": \"a.example.com\", \"group\": \"us\"}\n  ]\n}";
+        let error = config_err(text, ConfigFormat::Json);
+        assert_eq!((error.line, error.column), (Some(4), Some(44)));
+        assert_eq!(error.message, "servers[0]: unknown group `us`");
+        assert_eq!(
+            error.to_string(),
+            "line 4:44: servers[0]: unknown group `us`"
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_errors_report_line_and_column() {
+        let text = "[[servers]]\naddress = \"a.example.com\"\nedition = \"pocket\"\n";
+        let error = config_err(text, ConfigFormat::Toml);
+        assert_eq!((error.line, error.column), (Some(3), Some(11)));
+        assert!(error.message.contains("unknown edition `pocket`"));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_errors_report_line_and_column() {
+        let text = "servers:\n  - address: a.example.com\n    interval: 5 days\n";
+        let error = config_err(text, ConfigFormat::Yaml);
+        assert_eq!(error.line, Some(3));
+        assert!(error.message.contains("invalid duration `5 days`"));
+    }
+}
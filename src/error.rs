@@ -1,21 +1,57 @@
 // Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
 
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use trust_dns_resolver::error::{ResolveError, ResolveErrorKind};
+use trust_dns_resolver::proto::op::ResponseCode;
+
+use crate::config::ConfigError;
 
 #[derive(Error, Debug)]
 pub enum McError {
-    #[error("DNS resolution failed: {0}")]
-    DnsError(String),
+    #[error("DNS resolution of {host} failed ({kind}): {message}")]
+    DnsError {
+        host: String,
+        kind: DnsErrorKind,
+        message: String,
+    },
 
-    #[error("Connection failed: {0}")]
-    ConnectionError(String),
+    #[error("Connection to {addr} failed during {phase} ({kind}): {message}")]
+    ConnectionError {
+        addr: SocketAddr,
+        phase: Phase,
+        kind: ConnectionErrorKind,
+        message: String,
+    },
 
-    #[error("Timeout occurred")]
-    Timeout,
+    #[error("Timeout occurred during {phase}")]
+    Timeout {
+        phase: Phase,
+        addr: Option<SocketAddr>,
+    },
 
     #[error("Invalid server response: {0}")]
     InvalidResponse(String),
 
+    #[error("Invalid VarInt: longer than 5 bytes")]
+    InvalidVarInt,
+
+    #[error("Invalid length in server response: {0}")]
+    InvalidLength(i32),
+
+    #[error("Server response of {size} bytes exceeds the limit of {limit} bytes")]
+    ResponseTooLarge { size: usize, limit: usize },
+
+    #[error("Unexpected packet ID: {0}")]
+    UnexpectedPacketId(i32),
+
+    #[error("Invalid packet: {0}")]
+    InvalidPacket(String),
+
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -36,4 +72,307 @@ pub enum McError {
 
     #[error("Invalid address format: {0}")]
     InvalidAddress(String),
+
+    #[error("Checkpoint error: {0}")]
+    Checkpoint(String),
+
+    #[error("History store error: {0}")]
+    History(String),
+
+    #[error("Invalid NBT data: {0}")]
+    Nbt(String),
+
+    #[error("Invalid server list: {0}")]
+    Config(ConfigError),
+}
+
+/// The step of a ping that was running when an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Resolve,
+    Connect,
+    Handshake,
+    Read,
+    Parse,
+}
+
+/// Why a DNS lookup failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DnsErrorKind {
+    /// The domain does not exist (`NXDOMAIN`).
+    NxDomain,
+    /// The domain exists but has no address records.
+    NoRecords,
+    /// The upstream resolver failed to answer (`SERVFAIL`).
+    ServFail,
+    /// The upstream resolver refused the query (`REFUSED`).
+    Refused,
+    /// No answer arrived in time.
+    Timeout,
+    Other,
+}
+
+/// Why a socket operation against the server failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionErrorKind {
+    Refused,
+    Reset,
+    Aborted,
+    /// The peer closed the connection before the exchange finished.
+    Closed,
+    HostUnreachable,
+    NetworkUnreachable,
+    AddressUnavailable,
+    PermissionDenied,
+    TimedOut,
+    Other,
+}
+
+/// Coarse classification of an [`McError`], used to decide which failures are worth retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErrorKind {
+    Dns,
+    Connection,
+    Timeout,
+    InvalidResponse,
+    /// The server broke the wire protocol (bad VarInt, length or packet ID, oversized payload).
+    Protocol,
+    Io,
+    Json,
+    Utf8,
+    Base64,
+    InvalidEdition,
+    InvalidPort,
+    InvalidAddress,
+    Checkpoint,
+    History,
+    Nbt,
+    Config,
+}
+
+/// A serializable snapshot of an [`McError`], suitable for storing failures alongside results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorRecord {
+    pub kind: ErrorKind,
+    pub phase: Option<Phase>,
+    pub address: Option<SocketAddr>,
+    pub host: Option<String>,
+    pub dns: Option<DnsErrorKind>,
+    pub connection: Option<ConnectionErrorKind>,
+    pub message: String,
+    pub retryable: bool,
+    pub offline: bool,
+}
+
+impl McError {
+    pub(crate) fn dns(host: &str, error: &ResolveError) -> Self {
+        McError::DnsError {
+            host: host.to_string(),
+            kind: DnsErrorKind::from(error),
+            message: error.to_string(),
+        }
+    }
+
+    pub(crate) fn connection(addr: SocketAddr, phase: Phase, error: io::Error) -> Self {
+        McError::ConnectionError {
+            addr,
+            phase,
+            kind: ConnectionErrorKind::from(&error),
+            message: error.to_string(),
+        }
+    }
+
+    pub(crate) fn timeout(phase: Phase, addr: Option<SocketAddr>) -> Self {
+        McError::Timeout { phase, addr }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            McError::DnsError { .. } => ErrorKind::Dns,
+            McError::ConnectionError { .. } => ErrorKind::Connection,
+            McError::Timeout { .. } => ErrorKind::Timeout,
+            McError::InvalidResponse(_) => ErrorKind::InvalidResponse,
+            McError::InvalidVarInt
+            | McError::InvalidLength(_)
+            | McError::ResponseTooLarge { .. }
+            | McError::UnexpectedPacketId(_)
+            | McError::InvalidPacket(_) => ErrorKind::Protocol,
+            McError::IoError(_) => ErrorKind::Io,
+            McError::JsonError(_) => ErrorKind::Json,
+            McError::Utf8Error(_) => ErrorKind::Utf8,
+            McError::Base64Error(_) => ErrorKind::Base64,
+            McError::InvalidEdition(_) => ErrorKind::InvalidEdition,
+            McError::InvalidPort(_) => ErrorKind::InvalidPort,
+            McError::InvalidAddress(_) => ErrorKind::InvalidAddress,
+            McError::Checkpoint(_) => ErrorKind::Checkpoint,
+            McError::History(_) => ErrorKind::History,
+            McError::Nbt(_) => ErrorKind::Nbt,
+            McError::Config(_) => ErrorKind::Config,
+        }
+    }
+
+    /// The step of the ping that failed, if the error came from a ping.
+    pub fn phase(&self) -> Option<Phase> {
+        match self {
+            McError::DnsError { .. } => Some(Phase::Resolve),
+            McError::ConnectionError { phase, .. } | McError::Timeout { phase, .. } => Some(*phase),
+            McError::ResponseTooLarge { .. } => Some(Phase::Read),
+            McError::InvalidResponse(_)
+            | McError::InvalidVarInt
+            | McError::InvalidLength(_)
+            | McError::UnexpectedPacketId(_)
+            | McError::InvalidPacket(_)
+            | McError::JsonError(_)
+            | McError::Utf8Error(_) => Some(Phase::Parse),
+            _ => None,
+        }
+    }
+
+    /// The server address involved in the failure, if one had been resolved.
+    pub fn address(&self) -> Option<SocketAddr> {
+        match self {
+            McError::ConnectionError { addr, .. } => Some(*addr),
+            McError::Timeout { addr, .. } => *addr,
+            _ => None,
+        }
+    }
+
+    /// Whether the failure is likely transient, so trying again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            McError::Timeout { .. } | McError::IoError(_) => true,
+            McError::DnsError { kind, .. } => matches!(
+                kind,
+                DnsErrorKind::ServFail | DnsErrorKind::Timeout | DnsErrorKind::Other
+            ),
+            McError::ConnectionError { kind, .. } => matches!(
+                kind,
+                ConnectionErrorKind::Reset
+                    | ConnectionErrorKind::Aborted
+                    | ConnectionErrorKind::Closed
+                    | ConnectionErrorKind::TimedOut
+                    | ConnectionErrorKind::AddressUnavailable
+                    | ConnectionErrorKind::Other
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether the server could not be reached at all, as opposed to answering with
+    /// something invalid or the request itself being malformed.
+    pub fn is_offline(&self) -> bool {
+        matches!(
+            self,
+            McError::DnsError { .. } | McError::ConnectionError { .. } | McError::Timeout { .. }
+        )
+    }
+
+    pub fn to_record(&self) -> ErrorRecord {
+        ErrorRecord::from(self)
+    }
+}
+
+impl From<&McError> for ErrorRecord {
+    fn from(error: &McError) -> Self {
+        let (host, dns) = match error {
+            McError::DnsError { host, kind, .. } => (Some(host.clone()), Some(*kind)),
+            _ => (None, None),
+        };
+        let connection = match error {
+            McError::ConnectionError { kind, .. } => Some(*kind),
+            _ => None,
+        };
+
+        ErrorRecord {
+            kind: error.kind(),
+            phase: error.phase(),
+            address: error.address(),
+            host,
+            dns,
+            connection,
+            message: error.to_string(),
+            retryable: error.is_retryable(),
+            offline: error.is_offline(),
+        }
+    }
+}
+
+impl From<&ResolveError> for DnsErrorKind {
+    fn from(error: &ResolveError) -> Self {
+        match error.kind() {
+            ResolveErrorKind::NoRecordsFound { response_code, .. } => match *response_code {
+                ResponseCode::NXDomain => DnsErrorKind::NxDomain,
+                ResponseCode::NoError => DnsErrorKind::NoRecords,
+                ResponseCode::ServFail => DnsErrorKind::ServFail,
+                ResponseCode::Refused => DnsErrorKind::Refused,
+                _ => DnsErrorKind::Other,
+            },
+            ResolveErrorKind::Timeout => DnsErrorKind::Timeout,
+            _ => DnsErrorKind::Other,
+        }
+    }
+}
+
+impl From<&io::Error> for ConnectionErrorKind {
+    fn from(error: &io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::ConnectionRefused => ConnectionErrorKind::Refused,
+            io::ErrorKind::ConnectionReset => ConnectionErrorKind::Reset,
+            io::ErrorKind::ConnectionAborted => ConnectionErrorKind::Aborted,
+            io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof => ConnectionErrorKind::Closed,
+            io::ErrorKind::HostUnreachable => ConnectionErrorKind::HostUnreachable,
+            io::ErrorKind::NetworkUnreachable => ConnectionErrorKind::NetworkUnreachable,
+            io::ErrorKind::AddrNotAvailable => ConnectionErrorKind::AddressUnavailable,
+            io::ErrorKind::PermissionDenied => ConnectionErrorKind::PermissionDenied,
+            io::ErrorKind::TimedOut => ConnectionErrorKind::TimedOut,
+            _ => ConnectionErrorKind::Other,
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::Resolve => "resolve",
+            Phase::Connect => "connect",
+            Phase::Handshake => "handshake",
+            Phase::Read => "read",
+            Phase::Parse => "parse",
+        })
+    }
+}
+
+impl fmt::Display for DnsErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DnsErrorKind::NxDomain => "NXDOMAIN",
+            DnsErrorKind::NoRecords => "no records",
+            DnsErrorKind::ServFail => "SERVFAIL",
+            DnsErrorKind::Refused => "REFUSED",
+            DnsErrorKind::Timeout => "timed out",
+            DnsErrorKind::Other => "other",
+        })
+    }
+}
+
+impl fmt::Display for ConnectionErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConnectionErrorKind::Refused => "refused",
+            ConnectionErrorKind::Reset => "reset by peer",
+            ConnectionErrorKind::Aborted => "aborted",
+            ConnectionErrorKind::Closed => "closed by peer",
+            ConnectionErrorKind::HostUnreachable => "host unreachable",
+            ConnectionErrorKind::NetworkUnreachable => "network unreachable",
+            ConnectionErrorKind::AddressUnavailable => "address unavailable",
+            ConnectionErrorKind::PermissionDenied => "permission denied",
+            ConnectionErrorKind::TimedOut => "timed out",
+            ConnectionErrorKind::Other => "other",
+        })
+    }
 }
@@ -0,0 +1,31 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! File helpers shared by the modules that save state to disk.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::random::random_u64;
+
+/// Replaces the contents of `path` with `bytes` so that a crash mid-write leaves either the
+/// old file or the new one, never a mix.
+///
+/// The bytes go to a uniquely named file next to `path` that is then renamed over it, so
+/// concurrent writers of the same path do not clobber each other's half-written data.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{:016x}.tmp", random_u64()));
+    let tmp = PathBuf::from(tmp);
+
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp)
+        .and_then(|mut file| file.write_all(bytes))
+        .and_then(|()| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
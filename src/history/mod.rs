@@ -0,0 +1,139 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Storage for the results of repeated pings, queried by server and time range.
+//!
+//! [`NdjsonStore`] appends one JSON record per line to a file. [`SqliteStore`], behind the
+//! `sqlite` feature, keeps records in an embedded SQLite database.
+
+mod ndjson;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use std::ops::Range;
+use std::time::SystemTime;
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ErrorRecord, McError};
+use crate::models::{PingResult, ServerData, ServerEdition};
+
+pub use ndjson::NdjsonStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+/// One ping of one server, reduced to what is worth keeping over time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// The address the server was pinged by, used as its key.
+    pub server: String,
+    pub edition: ServerEdition,
+    pub at: SystemTime,
+    pub online: bool,
+    pub latency: Option<f64>,
+    pub players_online: Option<i64>,
+    pub players_max: Option<i64>,
+    pub version: Option<String>,
+    pub error: Option<ErrorRecord>,
+}
+
+impl From<&PingResult> for HistoryRecord {
+    fn from(result: &PingResult) -> Self {
+        let (players_online, players_max, version) = match result.data() {
+            Some(ServerData::Java(java)) => (
+                Some(java.players.online),
+                Some(java.players.max),
+                Some(java.version.name.clone()),
+            ),
+            Some(ServerData::Bedrock(bedrock)) => (
+                bedrock.online_players.parse().ok(),
+                bedrock.max_players.parse().ok(),
+                Some(bedrock.version.clone()),
+            ),
+            None => (None, None, None),
+        };
+
+        Self {
+            server: result.target.address.clone(),
+            edition: result.target.edition,
+            at: result.started_at,
+            online: result.is_online(),
+            latency: result.latency,
+            players_online,
+            players_max,
+            version,
+            error: result.offline_reason().cloned(),
+        }
+    }
+}
+
+/// Somewhere to keep [`HistoryRecord`]s.
+pub trait HistoryStore: Send + Sync {
+    fn append<'a>(&'a self, record: &'a HistoryRecord) -> BoxFuture<'a, Result<(), McError>>;
+
+    /// Records of `server` taken within `range`, oldest first.
+    fn query<'a>(
+        &'a self,
+        server: &'a str,
+        range: Range<SystemTime>,
+    ) -> BoxFuture<'a, Result<Vec<HistoryRecord>, McError>>;
+
+    /// Every server with at least one record, sorted.
+    fn servers(&self) -> BoxFuture<'_, Result<Vec<String>, McError>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    pub(super) fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs)
+    }
+
+    pub(super) fn record(server: &str, secs: u64, online: bool) -> HistoryRecord {
+        HistoryRecord {
+            server: server.to_string(),
+            edition: ServerEdition::Java,
+            at: at(secs),
+            online,
+            latency: online.then_some(20.5),
+            players_online: online.then_some(3),
+            players_max: online.then_some(20),
+            version: online.then(|| "1.21".to_string()),
+            error: (!online).then(|| McError::InvalidAddress(server.to_string()).to_record()),
+        }
+    }
+
+    /// Stores records out of order for two servers and checks they come back per server,
+    /// oldest first, within the half-open range.
+    pub(super) async fn check_store(store: &dyn HistoryStore) {
+        let records = [
+            record("b.test", 30, true),
+            record("a.test", 20, false),
+            record("a.test", 10, true),
+            record("a.test", 40, true),
+        ];
+        for record in &records {
+            store.append(record).await.unwrap();
+        }
+
+        assert_eq!(store.servers().await.unwrap(), ["a.test", "b.test"]);
+        assert_eq!(
+            store.query("a.test", at(0)..at(100)).await.unwrap(),
+            [records[2].clone(), records[1].clone(), records[3].clone()]
+        );
+        assert_eq!(
+            store.query("a.test", at(10)..at(40)).await.unwrap(),
+            [records[2].clone(), records[1].clone()]
+        );
+        assert!(
+            store
+                .query("c.test", at(0)..at(100))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use futures::future::BoxFuture;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+
+use super::{HistoryRecord, HistoryStore};
+use crate::error::McError;
+
+/// An append-only file with one JSON [`HistoryRecord`] per line.
+///
+/// Queries read the whole file, so this suits modest histories or files that are rotated
+/// externally. Lines that do not parse, such as one cut short by a crash, are skipped.
+pub struct NdjsonStore {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl NdjsonStore {
+    /// Opens `path` for appending, creating it if needed.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, McError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn for_each(&self, mut f: impl FnMut(HistoryRecord)) -> Result<(), McError> {
+        let mut lines = BufReader::new(File::open(&self.path).await?).lines();
+        while let Some(line) = lines.next_line().await? {
+            if let Ok(record) = serde_json::from_str(&line) {
+                f(record);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl HistoryStore for NdjsonStore {
+    fn append<'a>(&'a self, record: &'a HistoryRecord) -> BoxFuture<'a, Result<(), McError>> {
+        Box::pin(async move {
+            let mut line = serde_json::to_vec(record)?;
+            line.push(b'\n');
+            let mut file = self.file.lock().await;
+            file.write_all(&line).await?;
+            file.flush().await?;
+            Ok(())
+        })
+    }
+
+    fn query<'a>(
+        &'a self,
+        server: &'a str,
+        range: Range<SystemTime>,
+    ) -> BoxFuture<'a, Result<Vec<HistoryRecord>, McError>> {
+        Box::pin(async move {
+            let mut records = Vec::new();
+            self.for_each(|record| {
+                if record.server == server && range.contains(&record.at) {
+                    records.push(record);
+                }
+            })
+            .await?;
+            records.sort_by_key(|record| record.at);
+            Ok(records)
+        })
+    }
+
+    fn servers(&self) -> BoxFuture<'_, Result<Vec<String>, McError>> {
+        Box::pin(async move {
+            let mut servers = BTreeSet::new();
+            self.for_each(|record| {
+                servers.insert(record.server);
+            })
+            .await?;
+            Ok(servers.into_iter().collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::tests::{check_store, record};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("mc-history-{name}-{}.ndjson", std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn round_trips_and_queries_ranges() {
+        let path = temp_path("round-trip");
+        let _ = std::fs::remove_file(&path);
+        check_store(&NdjsonStore::open(&path).await.unwrap()).await;
+
+        // Records survive reopening the file.
+        let reopened = NdjsonStore::open(&path).await.unwrap();
+        assert_eq!(reopened.servers().await.unwrap(), ["a.test", "b.test"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn skips_unreadable_lines() {
+        let path = temp_path("torn");
+        let line = serde_json::to_string(&record("a.test", 10, true)).unwrap();
+        std::fs::write(&path, format!("{line}\n{{\"server\":\"a.te")).unwrap();
+
+        let store = NdjsonStore::open(&path).await.unwrap();
+        assert_eq!(store.servers().await.unwrap(), ["a.test"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future::BoxFuture;
+use rusqlite::{Connection, Row, params};
+
+use super::{HistoryRecord, HistoryStore};
+use crate::error::McError;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS history (
+        id INTEGER PRIMARY KEY,
+        server TEXT NOT NULL,
+        edition TEXT NOT NULL,
+        at_ms INTEGER NOT NULL,
+        online INTEGER NOT NULL,
+        latency REAL,
+        players_online INTEGER,
+        players_max INTEGER,
+        version TEXT,
+        error TEXT
+    );
+    CREATE INDEX IF NOT EXISTS history_server_at ON history (server, at_ms);
+";
+
+/// [`HistoryRecord`]s in an embedded SQLite database, indexed by server and time.
+///
+/// Statements run on tokio's blocking pool. Errors are stored as JSON text.
+#[derive(Clone)]
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens or creates the database at `path`.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, McError> {
+        let path = path.as_ref().to_path_buf();
+        Self::init(move || Connection::open(path)).await
+    }
+
+    /// A database that lives only as long as the store.
+    pub async fn open_in_memory() -> Result<Self, McError> {
+        Self::init(Connection::open_in_memory).await
+    }
+
+    async fn init(
+        open: impl FnOnce() -> rusqlite::Result<Connection> + Send + 'static,
+    ) -> Result<Self, McError> {
+        let connection = blocking(move || {
+            let connection = open()?;
+            connection.execute_batch(SCHEMA)?;
+            Ok(connection)
+        })
+        .await?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    async fn with_connection<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> Result<T, McError> {
+        let connection = self.connection.clone();
+        blocking(move || f(&connection.lock().unwrap())).await
+    }
+}
+
+impl HistoryStore for SqliteStore {
+    fn append<'a>(&'a self, record: &'a HistoryRecord) -> BoxFuture<'a, Result<(), McError>> {
+        Box::pin(async move {
+            let record = record.clone();
+            let error = record
+                .error
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?;
+            self.with_connection(move |connection| {
+                connection.execute(
+                    "INSERT INTO history (server, edition, at_ms, online, latency, players_online,
+                         players_max, version, error)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        record.server,
+                        format!("{:?}", record.edition),
+                        to_millis(record.at),
+                        record.online,
+                        record.latency,
+                        record.players_online,
+                        record.players_max,
+                        record.version,
+                        error,
+                    ],
+                )
+            })
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn query<'a>(
+        &'a self,
+        server: &'a str,
+        range: Range<SystemTime>,
+    ) -> BoxFuture<'a, Result<Vec<HistoryRecord>, McError>> {
+        Box::pin(async move {
+            let server = server.to_string();
+            let (start, end) = (to_millis(range.start), to_millis(range.end));
+            self.with_connection(move |connection| {
+                let mut statement = connection.prepare(
+                    "SELECT server, edition, at_ms, online, latency, players_online, players_max,
+                         version, error
+                     FROM history WHERE server = ?1 AND at_ms >= ?2 AND at_ms < ?3
+                     ORDER BY at_ms, id",
+                )?;
+                statement
+                    .query_map(params![server, start, end], from_row)?
+                    .collect()
+            })
+            .await
+        })
+    }
+
+    fn servers(&self) -> BoxFuture<'_, Result<Vec<String>, McError>> {
+        Box::pin(self.with_connection(|connection| {
+            let mut statement =
+                connection.prepare("SELECT DISTINCT server FROM history ORDER BY server")?;
+            statement.query_map([], |row| row.get(0))?.collect()
+        }))
+    }
+}
+
+fn from_row(row: &Row<'_>) -> rusqlite::Result<HistoryRecord> {
+    let edition: String = row.get(1)?;
+    let error: Option<String> = row.get(8)?;
+    Ok(HistoryRecord {
+        server: row.get(0)?,
+        edition: edition.parse().map_err(|_| {
+            rusqlite::Error::InvalidColumnType(1, "edition".into(), rusqlite::types::Type::Text)
+        })?,
+        at: UNIX_EPOCH + Duration::from_millis(row.get::<_, i64>(2)?.max(0) as u64),
+        online: row.get(3)?,
+        latency: row.get(4)?,
+        players_online: row.get(5)?,
+        players_max: row.get(6)?,
+        version: row.get(7)?,
+        error: error.and_then(|error| serde_json::from_str(&error).ok()),
+    })
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> rusqlite::Result<T> + Send + 'static,
+) -> Result<T, McError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| McError::History(e.to_string()))?
+        .map_err(|e| McError::History(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::tests::check_store;
+
+    #[tokio::test]
+    async fn round_trips_and_queries_ranges() {
+        check_store(&SqliteStore::open_in_memory().await.unwrap()).await;
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+use tokio::time::{Instant, timeout_at};
+
+use crate::client::unix_millis;
+use crate::error::McError;
+use crate::models::BedrockLanServer;
+use crate::protocol::bedrock;
+
+/// Link-local all-nodes group, used in place of broadcast on IPv6.
+const ALL_NODES_V6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+
+/// Where and how long [`McClient::discover_bedrock`](crate::McClient::discover_bedrock)
+/// looks for Bedrock games on the local network.
+#[derive(Debug, Clone)]
+pub struct BedrockDiscovery {
+    /// How long to collect pongs after the pings are sent.
+    pub window: Duration,
+    pub ports: Vec<u16>,
+    /// IPv4 interfaces to broadcast from, by address.
+    pub interfaces: Vec<Ipv4Addr>,
+    /// IPv6 interfaces to multicast from, by index (0 for the default).
+    pub ipv6_interfaces: Vec<u32>,
+    /// Extra addresses to ping, e.g. a subnet's directed broadcast address.
+    pub targets: Vec<IpAddr>,
+}
+
+impl Default for BedrockDiscovery {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(2),
+            ports: vec![19132, 19133],
+            interfaces: vec![Ipv4Addr::UNSPECIFIED],
+            ipv6_interfaces: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl BedrockDiscovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_ports(mut self, ports: &[u16]) -> Self {
+        self.ports = ports.to_vec();
+        self
+    }
+
+    /// Broadcast from these IPv4 interfaces instead of the default one.
+    pub fn with_interfaces(mut self, interfaces: &[Ipv4Addr]) -> Self {
+        self.interfaces = interfaces.to_vec();
+        self
+    }
+
+    /// Also multicast to `ff02::1` on this IPv6 interface.
+    pub fn with_ipv6_interface(mut self, index: u32) -> Self {
+        self.ipv6_interfaces.push(index);
+        self
+    }
+
+    pub fn with_target(mut self, target: IpAddr) -> Self {
+        self.targets.push(target);
+        self
+    }
+}
+
+/// Pings every configured destination and collects the pongs that arrive in the window,
+/// one per source address.
+pub(crate) async fn discover(
+    discovery: &BedrockDiscovery,
+) -> Result<Vec<BedrockLanServer>, McError> {
+    let mut sockets = Vec::new();
+
+    for interface in &discovery.interfaces {
+        let socket = udp_socket(Domain::IPV4, SocketAddr::from((*interface, 0)))?;
+        socket.set_broadcast(true)?;
+        let mut targets = vec![IpAddr::V4(Ipv4Addr::BROADCAST)];
+        targets.extend(discovery.targets.iter().filter(|t| t.is_ipv4()));
+        sockets.push((socket, targets));
+    }
+    for index in &discovery.ipv6_interfaces {
+        let socket = udp_socket(Domain::IPV6, SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)))?;
+        socket.set_multicast_if_v6(*index)?;
+        let mut targets = vec![IpAddr::V6(ALL_NODES_V6)];
+        targets.extend(discovery.targets.iter().filter(|t| t.is_ipv6()));
+        sockets.push((socket, targets));
+    }
+
+    let ping = bedrock::UnconnectedPing::new(unix_millis()).encode();
+    let sent_at = Instant::now();
+    let deadline = sent_at + discovery.window;
+
+    let mut bound = Vec::with_capacity(sockets.len());
+    for (socket, targets) in sockets {
+        let socket = UdpSocket::from_std(socket.into())?;
+        for ip in targets {
+            for port in &discovery.ports {
+                // One unreachable destination should not stop the others.
+                let _ = socket.send_to(&ping, SocketAddr::new(ip, *port)).await;
+            }
+        }
+        bound.push(socket);
+    }
+
+    let replies = futures::future::join_all(
+        bound
+            .iter()
+            .map(|socket| collect_pongs(socket, sent_at, deadline)),
+    )
+    .await;
+
+    let mut servers: Vec<BedrockLanServer> = Vec::new();
+    for server in replies.into_iter().flatten() {
+        if !servers.iter().any(|s| s.source == server.source) {
+            servers.push(server);
+        }
+    }
+    Ok(servers)
+}
+
+async fn collect_pongs(
+    socket: &UdpSocket,
+    sent_at: Instant,
+    deadline: Instant,
+) -> Vec<BedrockLanServer> {
+    let mut servers = Vec::new();
+    let mut buf = vec![0u8; bedrock::MAX_PONG_LEN];
+
+    while let Ok(received) = timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        // Errors such as ICMP port unreachable from one host are not fatal.
+        let Ok((len, source)) = received else {
+            continue;
+        };
+        let latency = sent_at.elapsed().as_secs_f64() * 1000.0;
+        let Ok(pong) = bedrock::UnconnectedPong::decode(&buf[..len]) else {
+            continue;
+        };
+        let Ok(status) = bedrock::parse_status(&pong.server_id) else {
+            continue;
+        };
+
+        servers.push(BedrockLanServer {
+            source,
+            latency,
+            status,
+        });
+    }
+    servers
+}
+
+fn udp_socket(domain: Domain, local: SocketAddr) -> std::io::Result<Socket> {
+    let socket = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&local.into())?;
+    Ok(socket)
+}
@@ -0,0 +1,174 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::{ToSocketAddrs, UdpSocket};
+use tokio::time::{Instant, sleep, timeout_at};
+
+use crate::client::McClient;
+use crate::models::{LanServer, ServerEdition, ServerInfo};
+use crate::protocol::java::LanAnnouncement;
+
+/// Multicast group Java Edition announces LAN worlds to.
+pub const LAN_MULTICAST_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 2, 60);
+pub const LAN_PORT: u16 = 4445;
+/// How often the game repeats its announcement.
+const ANNOUNCE_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// Listens for Java Edition LAN world announcements.
+pub struct JavaLanListener {
+    socket: UdpSocket,
+    client: Option<McClient>,
+}
+
+impl JavaLanListener {
+    /// Joins the LAN multicast group on the default interface.
+    pub async fn bind() -> io::Result<Self> {
+        Self::bind_on(Ipv4Addr::UNSPECIFIED).await
+    }
+
+    /// Joins the LAN multicast group on the interface with address `interface`. The port
+    /// is shared, so this works alongside a running game client.
+    pub async fn bind_on(interface: Ipv4Addr) -> io::Result<Self> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_reuse_address(true)?;
+        socket.set_nonblocking(true)?;
+        socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, LAN_PORT)).into())?;
+        socket.join_multicast_v4(&LAN_MULTICAST_ADDR, &interface)?;
+
+        Ok(Self {
+            socket: UdpSocket::from_std(socket.into())?,
+            client: None,
+        })
+    }
+
+    /// Listens on `addr` without joining the multicast group, for announcers sending to
+    /// it directly.
+    pub async fn bind_to(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(addr).await?,
+            client: None,
+        })
+    }
+
+    /// Ping each discovered server with `client`.
+    pub fn with_ping(mut self, client: McClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Waits for the next announcement. Servers repeat their announcement every
+    /// 1.5 seconds, so the same server is returned many times.
+    pub async fn next(&mut self) -> io::Result<LanServer> {
+        let mut server = self.next_announcement().await?;
+        if let Some(client) = &self.client {
+            server.ping = Some(client.probe(&server.info).await);
+        }
+        Ok(server)
+    }
+
+    /// Collects the servers announced within `window`, once each.
+    pub async fn discover(&mut self, window: Duration) -> io::Result<Vec<LanServer>> {
+        let deadline = Instant::now() + window;
+        let mut servers: Vec<LanServer> = Vec::new();
+
+        while let Ok(server) = timeout_at(deadline, self.next_announcement()).await {
+            let server = server?;
+            if !servers
+                .iter()
+                .any(|s| s.info.address == server.info.address)
+            {
+                servers.push(server);
+            }
+        }
+
+        if let Some(client) = &self.client {
+            let infos: Vec<ServerInfo> = servers.iter().map(|s| s.info.clone()).collect();
+            for result in client.ping_many(&infos).await {
+                if let Some(server) = servers
+                    .iter_mut()
+                    .find(|s| s.info.address == result.target.address)
+                {
+                    server.ping = Some(result);
+                }
+            }
+        }
+
+        Ok(servers)
+    }
+
+    async fn next_announcement(&self) -> io::Result<LanServer> {
+        let mut buf = [0u8; 1024];
+        loop {
+            let (len, source) = self.socket.recv_from(&mut buf).await?;
+            let Ok(announcement) = LanAnnouncement::decode(&buf[..len]) else {
+                continue;
+            };
+
+            let address = SocketAddr::new(source.ip(), announcement.port);
+            return Ok(LanServer {
+                info: ServerInfo::new(address.to_string(), ServerEdition::Java),
+                motd: announcement.motd,
+                source,
+                ping: None,
+            });
+        }
+    }
+}
+
+/// Announces a LAN world the way the game does, e.g. to exercise a [`JavaLanListener`].
+pub struct JavaLanAnnouncer {
+    announcement: LanAnnouncement,
+    target: SocketAddr,
+    interval: Duration,
+}
+
+impl JavaLanAnnouncer {
+    pub fn new(motd: &str, port: u16) -> Self {
+        Self {
+            announcement: LanAnnouncement {
+                motd: motd.to_string(),
+                port,
+            },
+            target: SocketAddr::from((LAN_MULTICAST_ADDR, LAN_PORT)),
+            interval: ANNOUNCE_INTERVAL,
+        }
+    }
+
+    /// Send announcements to `target` instead of the LAN multicast group.
+    pub fn with_target(mut self, target: SocketAddr) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sends a single announcement.
+    pub async fn announce(&self) -> io::Result<()> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        socket
+            .send_to(&self.announcement.encode(), self.target)
+            .await?;
+        Ok(())
+    }
+
+    /// Announces every interval until sending fails.
+    pub async fn run(self) -> io::Result<()> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        let packet = self.announcement.encode();
+        loop {
+            socket.send_to(&packet, self.target).await?;
+            sleep(self.interval).await;
+        }
+    }
+}
@@ -0,0 +1,9 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Discovery of servers announced on the local network.
+
+pub(crate) mod bedrock;
+mod java;
+
+pub use bedrock::BedrockDiscovery;
+pub use java::{JavaLanAnnouncer, JavaLanListener, LAN_MULTICAST_ADDR, LAN_PORT};
@@ -1,9 +1,46 @@
 // Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
 
+pub mod checkpoint;
 pub mod client;
+pub mod config;
 pub mod error;
+mod files;
+pub mod history;
+pub mod lan;
+pub mod limits;
 pub mod models;
+pub mod monitor;
+pub mod players;
+pub mod protocol;
+mod random;
+pub mod report;
+pub mod retry;
+pub mod scan;
+pub mod server;
+pub mod servers_dat;
+pub mod timeouts;
+pub mod transport;
 
+pub use checkpoint::Checkpoint;
 pub use client::McClient;
-pub use error::McError;
+pub use config::{ConfigError, ConfigFormat, ServerEntry, ServerList};
+pub use error::{ConnectionErrorKind, DnsErrorKind, ErrorKind, ErrorRecord, McError, Phase};
+#[cfg(feature = "sqlite")]
+pub use history::SqliteStore;
+pub use history::{HistoryRecord, HistoryStore, NdjsonStore};
+pub use lan::{BedrockDiscovery, JavaLanAnnouncer, JavaLanListener};
+pub use limits::RateLimits;
 pub use models::*;
+pub use monitor::{Change, Monitor, MonitorEvent, MonitorHandle, MonitorTarget};
+pub use players::{PlayerEvent, PlayerTracker, TrackedPlayer};
+pub use report::{LatencyStats, Outage, PlayerPeak, UptimeReport, WindowReport};
+pub use retry::RetryPolicy;
+pub use scan::Scan;
+#[cfg(feature = "http-api")]
+pub use server::StatusApi;
+pub use server::{BedrockStatusServer, JavaStatusServer, ServerHandle, StatusProxy};
+#[cfg(feature = "exporter")]
+pub use server::{Metrics, MetricsExporter};
+pub use servers_dat::{SavedServer, ServersDat};
+pub use timeouts::Timeouts;
+pub use transport::{MemoryTransport, TokioTransport, Transport};
@@ -0,0 +1,356 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use dashmap::DashMap;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{Instant, sleep_until};
+
+/// Expired host spacing entries are swept once every this many spaced pings.
+const SPACING_SWEEP_EVERY: usize = 1024;
+
+/// Politeness limits applied to every ping made through one [`McClient`](crate::McClient).
+///
+/// Limits are keyed by the resolved IP address, so many hostnames pointing at the same
+/// machine share one budget. The subnet limit groups IPv4 addresses by /24 and IPv6
+/// addresses by /64.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimits {
+    pub per_ip: Option<usize>,
+    pub per_subnet: Option<usize>,
+    pub queries_per_second: Option<f64>,
+    pub min_host_spacing: Option<Duration>,
+}
+
+impl RateLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of pings in flight to a single IP address.
+    pub fn with_per_ip_limit(mut self, limit: usize) -> Self {
+        self.per_ip = Some(limit.max(1));
+        self
+    }
+
+    /// Maximum number of pings in flight to a single /24 (IPv4) or /64 (IPv6) network.
+    pub fn with_per_subnet_limit(mut self, limit: usize) -> Self {
+        self.per_subnet = Some(limit.max(1));
+        self
+    }
+
+    /// Maximum number of pings started per second across all hosts.
+    pub fn with_queries_per_second(mut self, qps: f64) -> Self {
+        self.queries_per_second = (qps > 0.0).then_some(qps);
+        self
+    }
+
+    /// Minimum time between the start of two pings to the same IP address.
+    pub fn with_min_host_spacing(mut self, spacing: Duration) -> Self {
+        self.min_host_spacing = Some(spacing);
+        self
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Shared limiter state; cloned clients hold the same instance.
+#[derive(Debug, Default)]
+pub(crate) struct Limiter {
+    limits: RateLimits,
+    query_interval: Option<Duration>,
+    ip_slots: DashMap<IpAddr, Arc<Semaphore>>,
+    subnet_slots: DashMap<IpAddr, Arc<Semaphore>>,
+    next_host_start: DashMap<IpAddr, Instant>,
+    spaced_pings: AtomicUsize,
+    next_global_start: Mutex<Option<Instant>>,
+}
+
+/// Held for the duration of one network exchange with a host. Dropping the last permit
+/// for a host frees its semaphore.
+pub(crate) struct LimitPermit<'a> {
+    limiter: &'a Limiter,
+    ip: Option<(IpAddr, OwnedSemaphorePermit)>,
+    subnet: Option<(IpAddr, OwnedSemaphorePermit)>,
+}
+
+impl Limiter {
+    /// Rates that are zero, negative or not finite are treated as unlimited.
+    pub(crate) fn new(mut limits: RateLimits) -> Self {
+        let query_interval = limits
+            .queries_per_second
+            .filter(|qps| *qps > 0.0)
+            .and_then(|qps| Duration::try_from_secs_f64(qps.recip()).ok());
+        limits.queries_per_second = limits
+            .queries_per_second
+            .filter(|_| query_interval.is_some());
+        Self {
+            limits,
+            query_interval,
+            ..Self::default()
+        }
+    }
+
+    /// Waits until a ping to `ip` is allowed by every configured limit.
+    pub(crate) async fn acquire(&self, ip: IpAddr) -> LimitPermit<'_> {
+        let mut permit = LimitPermit {
+            limiter: self,
+            ip: None,
+            subnet: None,
+        };
+        if self.limits.is_unlimited() {
+            return permit;
+        }
+
+        if let Some(limit) = self.limits.per_ip {
+            let slot = Self::acquire_slot(&self.ip_slots, ip, limit).await;
+            permit.ip = Some((ip, slot));
+        }
+        if let Some(limit) = self.limits.per_subnet {
+            let subnet = subnet_of(ip);
+            let slot = Self::acquire_slot(&self.subnet_slots, subnet, limit).await;
+            permit.subnet = Some((subnet, slot));
+        }
+
+        if let Some(spacing) = self.limits.min_host_spacing {
+            let start = {
+                let now = Instant::now();
+                let count = self.spaced_pings.fetch_add(1, Ordering::Relaxed);
+                if count.is_multiple_of(SPACING_SWEEP_EVERY) {
+                    self.sweep_spacing(now);
+                }
+                let mut next = self.next_host_start.entry(ip).or_insert(now);
+                let start = (*next).max(now);
+                *next = start + spacing;
+                start
+            };
+            sleep_until(start).await;
+        }
+
+        if let Some(interval) = self.query_interval {
+            let start = {
+                let now = Instant::now();
+                let mut next = self.next_global_start.lock().unwrap();
+                let start = next.map_or(now, |n| n.max(now));
+                *next = Some(start + interval);
+                start
+            };
+            sleep_until(start).await;
+        }
+
+        permit
+    }
+
+    /// Drops hosts whose spacing has passed; they need no entry, so the map stays about
+    /// as large as the set of recently pinged hosts.
+    fn sweep_spacing(&self, now: Instant) {
+        self.next_host_start.retain(|_, next| *next > now);
+    }
+
+    async fn acquire_slot(
+        slots: &DashMap<IpAddr, Arc<Semaphore>>,
+        key: IpAddr,
+        limit: usize,
+    ) -> OwnedSemaphorePermit {
+        // Declared first so it runs last, after a cancelled wait has let go of the
+        // semaphore.
+        let _release = SlotGuard { slots, key };
+        let semaphore = slots
+            .entry(key)
+            .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+            .clone();
+        semaphore
+            .acquire_owned()
+            .await
+            .expect("limiter semaphores are never closed")
+    }
+
+    /// Removes the semaphore for `key` once nobody holds or waits on it.
+    fn release_slot(slots: &DashMap<IpAddr, Arc<Semaphore>>, key: IpAddr) {
+        slots.remove_if(&key, |_, semaphore| Arc::strong_count(semaphore) == 1);
+    }
+}
+
+/// Frees an unused semaphore when a wait for it ends, including by cancellation.
+struct SlotGuard<'a> {
+    slots: &'a DashMap<IpAddr, Arc<Semaphore>>,
+    key: IpAddr,
+}
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        Limiter::release_slot(self.slots, self.key);
+    }
+}
+
+impl Drop for LimitPermit<'_> {
+    fn drop(&mut self) {
+        if let Some((ip, slot)) = self.ip.take() {
+            drop(slot);
+            Limiter::release_slot(&self.limiter.ip_slots, ip);
+        }
+        if let Some((subnet, slot)) = self.subnet.take() {
+            drop(slot);
+            Limiter::release_slot(&self.limiter.subnet_slots, subnet);
+        }
+    }
+}
+
+fn subnet_of(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(v4) => IpAddr::V4(Ipv4Addr::from(v4.to_bits() & 0xFFFF_FF00)),
+        IpAddr::V6(v6) => IpAddr::V6(Ipv6Addr::from(v6.to_bits() & !((1u128 << 64) - 1))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::timeout;
+
+    const BLOCKED: Duration = Duration::from_millis(30);
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn per_ip_limit_caps_concurrency() {
+        let limiter = Limiter::new(RateLimits::new().with_per_ip_limit(2));
+        let first = limiter.acquire(ip("10.0.0.1")).await;
+        let _second = limiter.acquire(ip("10.0.0.1")).await;
+
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("10.0.0.1")))
+                .await
+                .is_err()
+        );
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("10.0.0.2")))
+                .await
+                .is_ok()
+        );
+
+        drop(first);
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("10.0.0.1")))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn per_subnet_limit_groups_neighbours() {
+        let limiter = Limiter::new(RateLimits::new().with_per_subnet_limit(1));
+        let _held = limiter.acquire(ip("10.0.0.1")).await;
+
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("10.0.0.200")))
+                .await
+                .is_err()
+        );
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("10.0.1.1")))
+                .await
+                .is_ok()
+        );
+
+        let _v6 = limiter.acquire(ip("2001:db8::1")).await;
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("2001:db8::ffff")))
+                .await
+                .is_err()
+        );
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("2001:db8:0:1::1")))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn queries_per_second_paces_all_hosts() {
+        let limiter = Limiter::new(RateLimits::new().with_queries_per_second(20.0));
+        let started = Instant::now();
+        for last in 1..=3 {
+            limiter.acquire(ip(&format!("10.0.0.{last}"))).await;
+        }
+        // The first ping starts at once, the next two 50ms apart.
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn host_spacing_applies_per_host() {
+        let spacing = Duration::from_millis(60);
+        let limiter = Limiter::new(RateLimits::new().with_min_host_spacing(spacing));
+        limiter.acquire(ip("10.0.0.1")).await;
+
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("10.0.0.2")))
+                .await
+                .is_ok()
+        );
+        let started = Instant::now();
+        limiter.acquire(ip("10.0.0.1")).await;
+        assert!(started.elapsed() >= spacing - BLOCKED);
+    }
+
+    #[tokio::test]
+    async fn expired_spacing_entries_are_swept() {
+        let spacing = Duration::from_millis(10);
+        let limiter = Limiter::new(RateLimits::new().with_min_host_spacing(spacing));
+        limiter.acquire(ip("10.0.0.1")).await;
+        limiter.acquire(ip("10.0.0.2")).await;
+        assert_eq!(limiter.next_host_start.len(), 2);
+
+        limiter.sweep_spacing(Instant::now() + spacing);
+        assert!(limiter.next_host_start.is_empty());
+    }
+
+    #[tokio::test]
+    async fn slots_are_released_after_use_and_cancellation() {
+        let limiter = Limiter::new(
+            RateLimits::new()
+                .with_per_ip_limit(1)
+                .with_per_subnet_limit(1),
+        );
+        let held = limiter.acquire(ip("10.0.0.1")).await;
+        // A waiter that gives up must not leave its slot behind.
+        assert!(
+            timeout(BLOCKED, limiter.acquire(ip("10.0.0.1")))
+                .await
+                .is_err()
+        );
+        assert_eq!(limiter.ip_slots.len(), 1);
+
+        drop(held);
+        assert!(limiter.ip_slots.is_empty());
+        assert!(limiter.subnet_slots.is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_query_rates_are_unlimited() {
+        for qps in [0.0, -1.0, f64::NAN, 1e-300] {
+            let limits = RateLimits {
+                queries_per_second: Some(qps),
+                ..RateLimits::new()
+            };
+            let limiter = Limiter::new(limits);
+            assert!(
+                timeout(BLOCKED, limiter.acquire(ip("10.0.0.1")))
+                    .await
+                    .is_ok()
+            );
+            assert!(
+                timeout(BLOCKED, limiter.acquire(ip("10.0.0.1")))
+                    .await
+                    .is_ok()
+            );
+        }
+    }
+}
@@ -3,12 +3,16 @@
 use std::fmt;
 use std::fs::File;
 use std::io::Write;
+use std::net::SocketAddr;
+use std::time::SystemTime;
 
 use base64::{Engine as _, engine::general_purpose};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::McError;
+use crate::error::ErrorRecord;
+use crate::timeouts::Timeouts;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ServerStatus {
@@ -17,6 +21,10 @@ pub struct ServerStatus {
     pub port: u16,
     pub hostname: String,
     pub latency: f64,
+    /// Number of attempts it took to get this response, including the successful one; 0
+    /// when read from data saved before attempts were counted.
+    #[serde(default)]
+    pub attempts: u32,
     pub dns: Option<DnsInfo>,
     pub data: ServerData,
 }
@@ -28,6 +36,51 @@ pub enum ServerData {
     Bedrock(BedrockStatus),
 }
 
+/// The outcome of pinging one server, whether or not it answered.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PingResult {
+    pub target: ServerInfo,
+    /// The address that was contacted, if resolution got that far.
+    pub address: Option<SocketAddr>,
+    pub started_at: SystemTime,
+    /// Total time spent on the ping in ms, including DNS and retries.
+    pub elapsed: f64,
+    /// Round-trip latency in ms, only known when the server answered.
+    pub latency: Option<f64>,
+    #[serde(default)]
+    pub attempts: u32,
+    pub dns: Option<DnsInfo>,
+    pub state: ServerState,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "state", content = "data", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum ServerState {
+    Online(ServerData),
+    Offline { reason: ErrorRecord },
+}
+
+/// A server found on the local network.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanServer {
+    pub info: ServerInfo,
+    pub motd: String,
+    /// Where the announcement came from.
+    pub source: SocketAddr,
+    /// The ping result, if the discovery was asked to ping.
+    pub ping: Option<PingResult>,
+}
+
+/// A Bedrock game that answered a LAN discovery ping.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BedrockLanServer {
+    /// Where the pong came from.
+    pub source: SocketAddr,
+    pub latency: f64,
+    pub status: BedrockStatus,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DnsInfo {
     pub a_records: Vec<String>,
@@ -35,7 +88,7 @@ pub struct DnsInfo {
     pub ttl: u32,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct JavaStatus {
     pub version: JavaVersion,
     pub players: JavaPlayers,
@@ -47,17 +100,19 @@ pub struct JavaStatus {
     pub software: Option<String>,
     pub plugins: Option<Vec<JavaPlugin>>,
     pub mods: Option<Vec<JavaMod>>,
+    /// Forge mod loader data, from `forgeData` (1.13+) or the legacy `modinfo` object.
+    pub forge_data: Option<ForgeData>,
     #[serde(skip)]
     pub raw_data: Value,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct JavaVersion {
     pub name: String,
     pub protocol: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct JavaPlayers {
     pub online: i64,
     pub max: i64,
@@ -82,7 +137,22 @@ pub struct JavaMod {
     pub version: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ForgeData {
+    /// FML network protocol version, or 0 for the legacy `modinfo` format.
+    pub fml_network_version: i64,
+    pub channels: Vec<ForgeChannel>,
+    pub mods: Vec<JavaMod>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForgeChannel {
+    pub res: String,
+    pub version: String,
+    pub required: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct BedrockStatus {
     pub edition: String,
     pub motd: String,
@@ -105,9 +175,16 @@ pub struct BedrockStatus {
 pub struct ServerInfo {
     pub address: String,
     pub edition: ServerEdition,
+    /// Overrides the client's timeouts for this server in `probe` and `ping_many`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeouts: Option<Timeouts>,
+    /// Host name sent in the Java handshake instead of the address's, for proxies that
+    /// route by host.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_host: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ServerEdition {
     Java,
     Bedrock,
@@ -124,6 +201,10 @@ impl fmt::Debug for JavaStatus {
             .field("software", &self.software)
             .field("plugins", &self.plugins.as_ref().map(|p| p.len()))
             .field("mods", &self.mods.as_ref().map(|m| m.len()))
+            .field(
+                "forge_data",
+                &self.forge_data.as_ref().map(|f| f.mods.len()),
+            )
             .field("favicon", &self.favicon.as_ref().map(|_| "[Favicon data]"))
             .field("raw_data", &"[Value]")
             .finish()
@@ -131,6 +212,38 @@ impl fmt::Debug for JavaStatus {
 }
 
 impl JavaStatus {
+    /// A status with the given version and MOTD and no players online.
+    pub fn new(version: &str, protocol: i64, description: &str) -> Self {
+        Self {
+            version: JavaVersion {
+                name: version.to_string(),
+                protocol,
+            },
+            description: description.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_players(mut self, online: i64, max: i64) -> Self {
+        self.players.online = online;
+        self.players.max = max;
+        self
+    }
+
+    /// Sets the favicon from raw PNG bytes.
+    pub fn with_favicon_png(mut self, png: &[u8]) -> Self {
+        self.favicon = Some(format!(
+            "data:image/png;base64,{}",
+            general_purpose::STANDARD.encode(png)
+        ));
+        self
+    }
+
+    pub fn with_forge_data(mut self, forge_data: ForgeData) -> Self {
+        self.forge_data = Some(forge_data);
+        self
+    }
+
     pub fn save_favicon(&self, filename: &str) -> Result<(), McError> {
         if let Some(favicon) = &self.favicon {
             let data = favicon.split(',').nth(1).unwrap_or(favicon);
@@ -148,6 +261,119 @@ impl JavaStatus {
     }
 }
 
+impl PingResult {
+    pub fn new(
+        target: ServerInfo,
+        started_at: SystemTime,
+        attempts: u32,
+        result: Result<ServerStatus, McError>,
+    ) -> Self {
+        let elapsed = started_at
+            .elapsed()
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0);
+
+        match result {
+            Ok(status) => Self {
+                target,
+                address: status.socket_addr(),
+                started_at,
+                elapsed,
+                latency: Some(status.latency),
+                attempts,
+                dns: status.dns,
+                state: ServerState::Online(status.data),
+            },
+            Err(error) => Self {
+                target,
+                address: error.address(),
+                started_at,
+                elapsed,
+                latency: None,
+                attempts,
+                dns: None,
+                state: ServerState::Offline {
+                    reason: error.to_record(),
+                },
+            },
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        matches!(self.state, ServerState::Online(_))
+    }
+
+    pub fn data(&self) -> Option<&ServerData> {
+        match &self.state {
+            ServerState::Online(data) => Some(data),
+            ServerState::Offline { .. } => None,
+        }
+    }
+
+    pub fn offline_reason(&self) -> Option<&ErrorRecord> {
+        match &self.state {
+            ServerState::Online(_) => None,
+            ServerState::Offline { reason } => Some(reason),
+        }
+    }
+}
+
+impl ServerInfo {
+    pub fn new(address: impl Into<String>, edition: ServerEdition) -> Self {
+        Self {
+            address: address.into(),
+            edition,
+            timeouts: None,
+            virtual_host: None,
+        }
+    }
+
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = Some(timeouts);
+        self
+    }
+
+    pub fn with_virtual_host(mut self, host: impl Into<String>) -> Self {
+        self.virtual_host = Some(host.into());
+        self
+    }
+}
+
+impl ServerStatus {
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        self.ip
+            .parse()
+            .ok()
+            .map(|ip| SocketAddr::new(ip, self.port))
+    }
+}
+
+impl BedrockStatus {
+    /// A Bedrock (`MCPE`) survival server status with no players online.
+    pub fn new(version: &str, protocol: i64, motd: &str) -> Self {
+        Self {
+            edition: "MCPE".to_string(),
+            motd: motd.to_string(),
+            protocol_version: protocol.to_string(),
+            version: version.to_string(),
+            online_players: "0".to_string(),
+            max_players: "0".to_string(),
+            server_uid: "0".to_string(),
+            game_mode: "Survival".to_string(),
+            game_mode_numeric: "1".to_string(),
+            port_ipv4: "19132".to_string(),
+            port_ipv6: "19133".to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_players(mut self, online: i64, max: i64) -> Self {
+        self.online_players = online.to_string();
+        self.max_players = max.to_string();
+        self
+    }
+}
+
 impl fmt::Debug for BedrockStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("BedrockStatus")
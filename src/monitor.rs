@@ -0,0 +1,418 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Polling servers and reporting what changed between polls.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval};
+
+use crate::client::McClient;
+use crate::error::ErrorRecord;
+use crate::history::{HistoryRecord, HistoryStore};
+use crate::models::{PingResult, ServerData, ServerInfo};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+const EVENT_BUFFER: usize = 256;
+
+/// A server to watch and what counts as a change worth reporting.
+#[derive(Debug, Clone)]
+pub struct MonitorTarget {
+    pub server: ServerInfo,
+    pub interval: Duration,
+    /// Player counts whose crossing, in either direction, is reported.
+    pub player_thresholds: Vec<i64>,
+    /// Latency in ms above which the server counts as degraded.
+    pub latency_threshold: Option<f64>,
+}
+
+impl MonitorTarget {
+    pub fn new(server: ServerInfo) -> Self {
+        Self {
+            server,
+            interval: DEFAULT_INTERVAL,
+            player_thresholds: Vec::new(),
+            latency_threshold: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_player_threshold(mut self, players: i64) -> Self {
+        self.player_thresholds.push(players);
+        self
+    }
+
+    pub fn with_latency_threshold(mut self, latency_ms: f64) -> Self {
+        self.latency_threshold = Some(latency_ms);
+        self
+    }
+}
+
+/// Something that changed on a monitored server between two polls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorEvent {
+    pub server: ServerInfo,
+    pub at: SystemTime,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Change {
+    WentOnline,
+    WentOffline { reason: ErrorRecord },
+    VersionChanged { from: String, to: String },
+    MotdChanged { from: String, to: String },
+    FaviconChanged,
+    PlayersAbove { threshold: i64, online: i64 },
+    PlayersBelow { threshold: i64, online: i64 },
+    LatencyDegraded { threshold: f64, latency: f64 },
+    LatencyRecovered { threshold: f64, latency: f64 },
+}
+
+/// Polls servers on their own intervals and reports changes as [`MonitorEvent`]s.
+///
+/// The first poll of each server only records its state; events describe changes after
+/// that. Details such as the version are compared with the last time the server was
+/// online, so a restart into a new version reports both `WentOnline` and `VersionChanged`.
+pub struct Monitor {
+    client: McClient,
+    targets: Vec<MonitorTarget>,
+    sinks: Sinks,
+}
+
+/// Where every poll result is recorded, besides being diffed into events.
+#[derive(Clone, Default)]
+struct Sinks {
+    history: Option<Arc<dyn HistoryStore>>,
+    #[cfg(feature = "exporter")]
+    metrics: Option<crate::server::Metrics>,
+}
+
+impl Sinks {
+    async fn record(&self, result: &PingResult) {
+        if let Some(history) = &self.history {
+            let _ = history.append(&HistoryRecord::from(result)).await;
+        }
+        #[cfg(feature = "exporter")]
+        if let Some(metrics) = &self.metrics {
+            metrics.record(result);
+        }
+    }
+}
+
+impl Monitor {
+    pub fn new(client: McClient) -> Self {
+        Self {
+            client,
+            targets: Vec::new(),
+            sinks: Sinks::default(),
+        }
+    }
+
+    pub fn with_target(mut self, target: MonitorTarget) -> Self {
+        self.targets.push(target);
+        self
+    }
+
+    /// Watch `server` with the default one-minute interval and no thresholds.
+    pub fn with_server(self, server: ServerInfo) -> Self {
+        self.with_target(MonitorTarget::new(server))
+    }
+
+    /// Append the result of every poll to `store`. Failed appends are ignored.
+    pub fn with_history(mut self, store: impl HistoryStore + 'static) -> Self {
+        self.sinks.history = Some(Arc::new(store));
+        self
+    }
+
+    /// Record the result of every poll into `metrics` for a
+    /// [`MetricsExporter`](crate::MetricsExporter) to serve.
+    #[cfg(feature = "exporter")]
+    pub fn with_metrics(mut self, metrics: crate::server::Metrics) -> Self {
+        self.sinks.metrics = Some(metrics);
+        self
+    }
+
+    /// Starts polling. Polling stops when the returned handle is dropped.
+    pub fn start(self) -> MonitorHandle {
+        let (sender, events) = mpsc::channel(EVENT_BUFFER);
+        let tasks = self
+            .targets
+            .into_iter()
+            .map(|target| {
+                tokio::spawn(poll(
+                    self.client.clone(),
+                    target,
+                    self.sinks.clone(),
+                    sender.clone(),
+                ))
+            })
+            .collect();
+
+        MonitorHandle { events, tasks }
+    }
+}
+
+/// Receives the events of a running [`Monitor`].
+pub struct MonitorHandle {
+    events: mpsc::Receiver<MonitorEvent>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MonitorHandle {
+    pub async fn recv(&mut self) -> Option<MonitorEvent> {
+        self.events.recv().await
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = MonitorEvent> {
+        futures::stream::unfold(self, |mut handle| async move {
+            let event = handle.recv().await?;
+            Some((event, handle))
+        })
+    }
+}
+
+impl Drop for MonitorHandle {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn poll(
+    client: McClient,
+    target: MonitorTarget,
+    sinks: Sinks,
+    sender: mpsc::Sender<MonitorEvent>,
+) {
+    let mut ticker = interval(target.interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut state = ServerState::default();
+
+    loop {
+        ticker.tick().await;
+        let result = client.probe(&target.server).await;
+        sinks.record(&result).await;
+
+        for change in state.update(&target, &result) {
+            let event = MonitorEvent {
+                server: target.server.clone(),
+                at: SystemTime::now(),
+                change,
+            };
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// What the monitor remembers about a server between polls.
+#[derive(Default)]
+struct ServerState {
+    online: Option<bool>,
+    version: Option<String>,
+    motd: Option<String>,
+    favicon: Option<String>,
+    players: Option<i64>,
+    degraded: bool,
+}
+
+impl ServerState {
+    fn update(&mut self, target: &MonitorTarget, result: &PingResult) -> Vec<Change> {
+        let mut changes = Vec::new();
+        let first_poll = self.online.is_none();
+
+        let Some(data) = result.data() else {
+            if self.online == Some(true)
+                && let Some(reason) = result.offline_reason()
+            {
+                changes.push(Change::WentOffline {
+                    reason: reason.clone(),
+                });
+            }
+            self.online = Some(false);
+            return changes;
+        };
+
+        if self.online == Some(false) {
+            changes.push(Change::WentOnline);
+        }
+        self.online = Some(true);
+
+        let (version, motd, favicon, players) = summarize(data);
+        let seen_online = self.version.is_some();
+        if let Some(from) = self.version.replace(version.clone())
+            && from != version
+        {
+            changes.push(Change::VersionChanged { from, to: version });
+        }
+        if let Some(from) = self.motd.replace(motd.clone())
+            && from != motd
+        {
+            changes.push(Change::MotdChanged { from, to: motd });
+        }
+        if seen_online && self.favicon != favicon {
+            changes.push(Change::FaviconChanged);
+        }
+        self.favicon = favicon;
+
+        if let Some(before) = self.players.replace(players) {
+            for &threshold in &target.player_thresholds {
+                if before < threshold && players >= threshold {
+                    changes.push(Change::PlayersAbove {
+                        threshold,
+                        online: players,
+                    });
+                } else if before >= threshold && players < threshold {
+                    changes.push(Change::PlayersBelow {
+                        threshold,
+                        online: players,
+                    });
+                }
+            }
+        }
+
+        if let (Some(threshold), Some(latency)) = (target.latency_threshold, result.latency) {
+            let degraded = latency > threshold;
+            if degraded != self.degraded {
+                changes.push(if degraded {
+                    Change::LatencyDegraded { threshold, latency }
+                } else {
+                    Change::LatencyRecovered { threshold, latency }
+                });
+            }
+            self.degraded = degraded;
+        }
+
+        if first_poll {
+            changes.clear();
+        }
+        changes
+    }
+}
+
+/// Version, MOTD, favicon and online player count of a response.
+fn summarize(data: &ServerData) -> (String, String, Option<String>, i64) {
+    match data {
+        ServerData::Java(java) => (
+            java.version.name.clone(),
+            java.description.clone(),
+            java.favicon.clone(),
+            java.players.online,
+        ),
+        ServerData::Bedrock(bedrock) => (
+            bedrock.version.clone(),
+            bedrock.motd.clone(),
+            None,
+            bedrock.online_players.parse().unwrap_or(0),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::McError;
+    use crate::models::{JavaStatus, ServerEdition, ServerStatus};
+
+    fn target() -> MonitorTarget {
+        MonitorTarget::new(ServerInfo::new("mc.test", ServerEdition::Java))
+            .with_player_threshold(10)
+    }
+
+    fn online(version: &str, players: i64) -> PingResult {
+        let mut java = JavaStatus::default();
+        java.version.name = version.to_string();
+        java.players.online = players;
+        let status = ServerStatus {
+            online: true,
+            ip: "10.0.0.1".to_string(),
+            port: 25565,
+            hostname: "mc.test".to_string(),
+            latency: 20.0,
+            attempts: 1,
+            dns: None,
+            data: ServerData::Java(java),
+        };
+        PingResult::new(target().server, SystemTime::now(), 1, Ok(status))
+    }
+
+    fn offline() -> PingResult {
+        let error = McError::InvalidAddress("mc.test".to_string());
+        PingResult::new(target().server, SystemTime::now(), 1, Err(error))
+    }
+
+    #[test]
+    fn first_poll_reports_nothing() {
+        let mut state = ServerState::default();
+        assert!(state.update(&target(), &online("1.21", 20)).is_empty());
+    }
+
+    #[test]
+    fn reports_going_offline_and_back() {
+        let target = target();
+        let mut state = ServerState::default();
+        state.update(&target, &online("1.21", 3));
+
+        let changes = state.update(&target, &offline());
+        assert!(matches!(changes.as_slice(), [Change::WentOffline { .. }]));
+        assert!(state.update(&target, &offline()).is_empty());
+        assert_eq!(
+            state.update(&target, &online("1.21", 3)),
+            [Change::WentOnline]
+        );
+    }
+
+    #[test]
+    fn compares_version_with_last_time_online() {
+        let target = target();
+        let mut state = ServerState::default();
+        state.update(&target, &online("1.20.4", 3));
+        state.update(&target, &offline());
+
+        assert_eq!(
+            state.update(&target, &online("1.21", 3)),
+            [
+                Change::WentOnline,
+                Change::VersionChanged {
+                    from: "1.20.4".to_string(),
+                    to: "1.21".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_player_threshold_crossings() {
+        let target = target();
+        let mut state = ServerState::default();
+        state.update(&target, &online("1.21", 3));
+
+        assert_eq!(
+            state.update(&target, &online("1.21", 12)),
+            [Change::PlayersAbove {
+                threshold: 10,
+                online: 12,
+            }]
+        );
+        assert!(state.update(&target, &online("1.21", 15)).is_empty());
+        assert_eq!(
+            state.update(&target, &online("1.21", 9)),
+            [Change::PlayersBelow {
+                threshold: 10,
+                online: 9,
+            }]
+        );
+    }
+}
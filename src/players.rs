@@ -0,0 +1,218 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Estimating who is online from the rotating player samples of Java servers.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::time::{Interval, MissedTickBehavior, interval};
+
+use crate::client::McClient;
+use crate::error::{ErrorRecord, McError};
+use crate::models::{JavaStatus, ServerData, ServerInfo, ServerState};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_LEAVE_CONFIDENCE: f64 = 0.05;
+/// Servers put text lines in the sample under this id; they are not players.
+const NIL_UUID: &str = "00000000-0000-0000-0000-000000000000";
+
+/// A player that has appeared in at least one sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackedPlayer {
+    pub name: String,
+    pub id: String,
+    pub first_seen: SystemTime,
+    pub last_seen: SystemTime,
+    /// Number of samples the player appeared in.
+    pub sightings: u32,
+    /// Estimated probability that the player is still online, given how often they
+    /// should have shown up in the samples since they were last seen.
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlayerEvent {
+    Joined { player: TrackedPlayer },
+    Left { player: TrackedPlayer },
+}
+
+/// Builds an estimated roster of a Java server by merging its player samples by UUID.
+///
+/// Servers only include a dozen or so players in each status, often a different subset
+/// every time, so a player missing from one sample has not necessarily left. Each miss
+/// lowers the player's confidence by the chance of being left out of that sample, and the
+/// player is reported as gone once it drops below the leave confidence. A sample that
+/// covers everyone online settles it immediately.
+///
+/// Players already online when tracking starts produce no `Joined` events. A player first
+/// seen later may still have been online for a while without being sampled.
+pub struct PlayerTracker {
+    client: McClient,
+    server: ServerInfo,
+    interval: Duration,
+    leave_confidence: f64,
+    players: HashMap<String, TrackedPlayer>,
+    online: i64,
+    polls: u64,
+}
+
+impl PlayerTracker {
+    /// Tracks `server`, which must be a Java server, with its own timeouts and virtual
+    /// host if it has them.
+    pub fn new(client: McClient, server: ServerInfo) -> Self {
+        Self {
+            client,
+            server,
+            interval: DEFAULT_INTERVAL,
+            leave_confidence: DEFAULT_LEAVE_CONFIDENCE,
+            players: HashMap::new(),
+            online: 0,
+            polls: 0,
+        }
+    }
+
+    /// Time between pings in [`into_stream`](Self::into_stream).
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Confidence below which an unseen player is considered gone.
+    pub fn with_leave_confidence(mut self, confidence: f64) -> Self {
+        self.leave_confidence = confidence;
+        self
+    }
+
+    /// Pings the server once and merges its sample into the roster. A failed ping
+    /// returns why it failed and leaves the roster unchanged.
+    pub async fn poll(&mut self) -> Result<Vec<PlayerEvent>, ErrorRecord> {
+        let result = self.client.probe(&self.server).await;
+        match result.state {
+            ServerState::Online(ServerData::Java(status)) => {
+                Ok(self.update(&status, SystemTime::now()))
+            }
+            ServerState::Online(ServerData::Bedrock(_)) => Err(McError::InvalidEdition(
+                "player samples are only available from Java servers".to_string(),
+            )
+            .to_record()),
+            ServerState::Offline { reason } => Err(reason),
+        }
+    }
+
+    /// Merges a status observed at `at` into the roster.
+    pub fn update(&mut self, status: &JavaStatus, at: SystemTime) -> Vec<PlayerEvent> {
+        let first_poll = self.polls == 0;
+        self.polls += 1;
+        self.online = status.players.online;
+
+        let sample: Vec<_> = status
+            .players
+            .sample
+            .iter()
+            .flatten()
+            .filter(|player| player.id != NIL_UUID)
+            .collect();
+
+        let mut events = Vec::new();
+        for player in &sample {
+            match self.players.get_mut(&player.id) {
+                Some(tracked) => {
+                    tracked.name = player.name.clone();
+                    tracked.last_seen = at;
+                    tracked.sightings += 1;
+                    tracked.confidence = 1.0;
+                }
+                None => {
+                    let tracked = TrackedPlayer {
+                        name: player.name.clone(),
+                        id: player.id.clone(),
+                        first_seen: at,
+                        last_seen: at,
+                        sightings: 1,
+                        confidence: 1.0,
+                    };
+                    if !first_poll {
+                        events.push(PlayerEvent::Joined {
+                            player: tracked.clone(),
+                        });
+                    }
+                    self.players.insert(player.id.clone(), tracked);
+                }
+            }
+        }
+
+        // Chance that an online player was left out of this sample.
+        let miss_chance = if self.online <= 0 {
+            0.0
+        } else if sample.is_empty() {
+            // The server hides its players; this ping says nothing about who left.
+            1.0
+        } else {
+            (1.0 - sample.len() as f64 / self.online as f64).max(0.0)
+        };
+
+        let leave_confidence = self.leave_confidence;
+        let mut left: Vec<_> = self
+            .players
+            .values_mut()
+            .filter(|tracked| !sample.iter().any(|player| player.id == tracked.id))
+            .filter_map(|tracked| {
+                tracked.confidence *= miss_chance;
+                (tracked.confidence < leave_confidence).then(|| tracked.id.clone())
+            })
+            .collect();
+        left.sort();
+        for id in left {
+            if let Some(player) = self.players.remove(&id) {
+                events.push(PlayerEvent::Left { player });
+            }
+        }
+        events
+    }
+
+    /// The players believed to be online, most recently seen first.
+    pub fn roster(&self) -> Vec<&TrackedPlayer> {
+        let mut roster: Vec<_> = self.players.values().collect();
+        roster.sort_by(|a, b| b.last_seen.cmp(&a.last_seen).then(a.name.cmp(&b.name)));
+        roster
+    }
+
+    /// Online player count reported by the last status.
+    pub fn online(&self) -> i64 {
+        self.online
+    }
+
+    /// Share of the reported online players that the roster accounts for, from 0 to 1.
+    pub fn coverage(&self) -> f64 {
+        if self.online <= 0 {
+            1.0
+        } else {
+            (self.players.len() as f64 / self.online as f64).min(1.0)
+        }
+    }
+
+    /// Polls on the configured interval, yielding events as they happen. Failed pings are
+    /// skipped and leave the roster unchanged.
+    pub fn into_stream(self) -> impl Stream<Item = PlayerEvent> {
+        // The ticker needs a runtime, so it is only created once the stream is polled.
+        futures::stream::unfold((self, None), |(mut tracker, ticker)| async move {
+            let mut ticker: Interval = ticker.unwrap_or_else(|| {
+                let mut ticker = interval(tracker.interval);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                ticker
+            });
+            loop {
+                ticker.tick().await;
+                if let Ok(events) = tracker.poll().await
+                    && !events.is_empty()
+                {
+                    return Some((futures::stream::iter(events), (tracker, Some(ticker))));
+                }
+            }
+        })
+        .flatten()
+    }
+}
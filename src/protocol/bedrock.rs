@@ -0,0 +1,223 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Bedrock Edition (RakNet) unconnected ping and pong packets.
+
+use crate::error::McError;
+use crate::models::BedrockStatus;
+
+pub const UNCONNECTED_PING_ID: u8 = 0x01;
+pub const UNCONNECTED_PONG_ID: u8 = 0x1C;
+
+/// The RakNet "offline message" magic that marks unconnected packets.
+pub const MAGIC: [u8; 16] = [
+    0x00, 0xFF, 0xFF, 0x00, 0xFE, 0xFE, 0xFE, 0xFE, 0xFD, 0xFD, 0xFD, 0xFD, 0x12, 0x34, 0x56, 0x78,
+];
+
+/// Size of a pong before its server ID string: ID, time, GUID, magic and string length.
+pub const PONG_HEADER_LEN: usize = 1 + 8 + 8 + 16 + 2;
+
+/// Size of the largest pong, whose server ID uses the whole 16-bit length.
+pub const MAX_PONG_LEN: usize = PONG_HEADER_LEN + u16::MAX as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnconnectedPing {
+    /// Client timestamp in milliseconds, echoed back in the pong.
+    pub time: u64,
+    pub client_guid: u64,
+}
+
+impl UnconnectedPing {
+    pub fn new(time: u64) -> Self {
+        Self {
+            time,
+            client_guid: 0,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(33);
+        packet.push(UNCONNECTED_PING_ID);
+        packet.extend_from_slice(&self.time.to_be_bytes());
+        packet.extend_from_slice(&MAGIC);
+        packet.extend_from_slice(&self.client_guid.to_be_bytes());
+        packet
+    }
+
+    pub fn decode(packet: &[u8]) -> Result<Self, McError> {
+        if packet.len() < 33 {
+            return Err(McError::InvalidPacket("Ping too short".to_string()));
+        }
+        if packet[0] != UNCONNECTED_PING_ID {
+            return Err(McError::UnexpectedPacketId(packet[0] as i32));
+        }
+        if packet[9..25] != MAGIC {
+            return Err(McError::InvalidPacket("Missing RakNet magic".to_string()));
+        }
+
+        Ok(Self {
+            time: read_u64(&packet[1..9]),
+            client_guid: read_u64(&packet[25..33]),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnconnectedPong {
+    pub time: u64,
+    pub server_guid: u64,
+    /// The semicolon-separated server ID string, parsed by [`parse_status`].
+    pub server_id: String,
+}
+
+impl UnconnectedPong {
+    pub fn encode(&self) -> Vec<u8> {
+        let server_id = self.server_id.as_bytes();
+        let len = server_id.len().min(u16::MAX as usize);
+
+        let mut packet = Vec::with_capacity(PONG_HEADER_LEN + len);
+        packet.push(UNCONNECTED_PONG_ID);
+        packet.extend_from_slice(&self.time.to_be_bytes());
+        packet.extend_from_slice(&self.server_guid.to_be_bytes());
+        packet.extend_from_slice(&MAGIC);
+        packet.extend_from_slice(&(len as u16).to_be_bytes());
+        packet.extend_from_slice(&server_id[..len]);
+        packet
+    }
+
+    /// Decodes a pong. Invalid UTF-8 in the server ID is replaced rather than rejected,
+    /// since many servers put raw formatting bytes in their MOTD.
+    pub fn decode(packet: &[u8]) -> Result<Self, McError> {
+        if packet.len() < PONG_HEADER_LEN {
+            return Err(McError::InvalidPacket("Pong too short".to_string()));
+        }
+        if packet[0] != UNCONNECTED_PONG_ID {
+            return Err(McError::UnexpectedPacketId(packet[0] as i32));
+        }
+        if packet[17..33] != MAGIC {
+            return Err(McError::InvalidPacket("Missing RakNet magic".to_string()));
+        }
+
+        let len = u16::from_be_bytes([packet[33], packet[34]]) as usize;
+        let end = PONG_HEADER_LEN + len;
+        if packet.len() < end {
+            return Err(McError::InvalidPacket(format!(
+                "Server ID of {len} bytes runs past the end of the pong"
+            )));
+        }
+
+        Ok(Self {
+            time: read_u64(&packet[1..9]),
+            server_guid: read_u64(&packet[9..17]),
+            server_id: String::from_utf8_lossy(&packet[PONG_HEADER_LEN..end]).to_string(),
+        })
+    }
+}
+
+/// Builds a [`BedrockStatus`] from a pong's server ID string.
+pub fn parse_status(server_id: &str) -> Result<BedrockStatus, McError> {
+    let parts: Vec<&str> = server_id.split(';').collect();
+
+    if parts.len() < 6 {
+        return Err(McError::InvalidResponse(
+            "Invalid Bedrock response".to_string(),
+        ));
+    }
+
+    Ok(BedrockStatus {
+        edition: parts[0].to_string(),
+        motd: parts[1].to_string(),
+        protocol_version: parts[2].to_string(),
+        version: parts[3].to_string(),
+        online_players: parts[4].to_string(),
+        max_players: parts[5].to_string(),
+        server_uid: parts.get(6).map_or("", |s| *s).to_string(),
+        motd2: parts.get(7).map_or("", |s| *s).to_string(),
+        game_mode: parts.get(8).map_or("", |s| *s).to_string(),
+        game_mode_numeric: parts.get(9).map_or("", |s| *s).to_string(),
+        port_ipv4: parts.get(10).map_or("", |s| *s).to_string(),
+        port_ipv6: parts.get(11).map_or("", |s| *s).to_string(),
+        map: parts.get(12).map(|s| s.to_string()),
+        software: parts.get(13).map(|s| s.to_string()),
+        raw_data: server_id.to_string(),
+    })
+}
+
+/// Builds the server ID string a pong carries for `status`, the inverse of
+/// [`parse_status`].
+pub fn server_id(status: &BedrockStatus) -> String {
+    let mut parts = vec![
+        status.edition.as_str(),
+        &status.motd,
+        &status.protocol_version,
+        &status.version,
+        &status.online_players,
+        &status.max_players,
+        &status.server_uid,
+        &status.motd2,
+        &status.game_mode,
+        &status.game_mode_numeric,
+        &status.port_ipv4,
+        &status.port_ipv6,
+    ];
+    if status.map.is_some() || status.software.is_some() {
+        parts.push(status.map.as_deref().unwrap_or_default());
+    }
+    if let Some(software) = &status.software {
+        parts.push(software);
+    }
+    parts.join(";")
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_be_bytes(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pong(server_id: &str) -> UnconnectedPong {
+        UnconnectedPong {
+            time: 42,
+            server_guid: 7,
+            server_id: server_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn pong_round_trips() {
+        let sent = pong("MCPE;Hello;712;1.21.0;1;10;123;World;Survival;1;19132;19133;");
+        assert_eq!(UnconnectedPong::decode(&sent.encode()).unwrap(), sent);
+    }
+
+    #[test]
+    fn pong_without_magic_is_rejected() {
+        let mut packet = pong("MCPE;Hello").encode();
+        packet[20] ^= 0xFF;
+        assert!(matches!(
+            UnconnectedPong::decode(&packet),
+            Err(McError::InvalidPacket(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_server_id_is_rejected() {
+        let packet = pong("MCPE;A rather long message of the day").encode();
+        for len in [PONG_HEADER_LEN - 1, PONG_HEADER_LEN + 3, packet.len() - 1] {
+            assert!(matches!(
+                UnconnectedPong::decode(&packet[..len]),
+                Err(McError::InvalidPacket(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn long_server_id_fits_the_largest_pong() {
+        let sent = pong(&"x".repeat(u16::MAX as usize));
+        let packet = sent.encode();
+        assert_eq!(packet.len(), MAX_PONG_LEN);
+        assert_eq!(UnconnectedPong::decode(&packet).unwrap(), sent);
+    }
+}
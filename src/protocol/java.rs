@@ -0,0 +1,475 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Java Edition handshake and status packets.
+
+use serde_json::{Map, Value, json};
+
+use super::{RawPacket, read_string, read_var_int, write_string, write_var_int};
+use crate::error::McError;
+use crate::models::*;
+
+/// Protocol version sent in handshakes; status requests work with any version.
+pub const DEFAULT_PROTOCOL_VERSION: i32 = 47;
+/// Longest server address the vanilla server accepts in a handshake.
+pub const MAX_ADDRESS_LEN: usize = 255 * 4;
+
+pub const HANDSHAKE_ID: i32 = 0x00;
+pub const STATUS_REQUEST_ID: i32 = 0x00;
+pub const STATUS_RESPONSE_ID: i32 = 0x00;
+pub const PING_ID: i32 = 0x01;
+pub const PONG_ID: i32 = 0x01;
+
+/// The state a client asks to switch to in its handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextState {
+    Status,
+    Login,
+    Other(i32),
+}
+
+impl From<i32> for NextState {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => NextState::Status,
+            2 => NextState::Login,
+            other => NextState::Other(other),
+        }
+    }
+}
+
+impl From<NextState> for i32 {
+    fn from(state: NextState) -> Self {
+        match state {
+            NextState::Status => 1,
+            NextState::Login => 2,
+            NextState::Other(other) => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handshake {
+    pub protocol_version: i32,
+    pub server_address: String,
+    pub server_port: u16,
+    pub next_state: NextState,
+}
+
+impl Handshake {
+    /// A handshake that asks for the status state, as sent by the server list screen.
+    pub fn status(server_address: &str, server_port: u16) -> Self {
+        Self {
+            protocol_version: DEFAULT_PROTOCOL_VERSION,
+            server_address: server_address.to_string(),
+            server_port,
+            next_state: NextState::Status,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(self.server_address.len() + 16);
+        write_var_int(&mut body, self.protocol_version);
+        write_string(&mut body, &self.server_address);
+        body.extend_from_slice(&self.server_port.to_be_bytes());
+        write_var_int(&mut body, self.next_state.into());
+        RawPacket::new(HANDSHAKE_ID, body).encode()
+    }
+
+    pub fn decode(packet: &RawPacket) -> Result<Self, McError> {
+        expect_id(packet, HANDSHAKE_ID)?;
+        let mut body = packet.body.as_slice();
+        let protocol_version = read_var_int(&mut body)?;
+        let server_address = read_string(&mut body, MAX_ADDRESS_LEN)?;
+        let port = body
+            .get(..2)
+            .ok_or_else(|| McError::InvalidPacket("Truncated handshake".to_string()))?;
+        let server_port = u16::from_be_bytes([port[0], port[1]]);
+        body = &body[2..];
+        let next_state = read_var_int(&mut body)?.into();
+
+        Ok(Self {
+            protocol_version,
+            server_address,
+            server_port,
+            next_state,
+        })
+    }
+}
+
+pub fn encode_status_request() -> Vec<u8> {
+    RawPacket::new(STATUS_REQUEST_ID, Vec::new()).encode()
+}
+
+pub fn decode_status_request(packet: &RawPacket) -> Result<(), McError> {
+    expect_id(packet, STATUS_REQUEST_ID)
+}
+
+pub fn encode_status_response(json: &str) -> Vec<u8> {
+    let mut body = Vec::with_capacity(json.len() + 5);
+    write_string(&mut body, json);
+    RawPacket::new(STATUS_RESPONSE_ID, body).encode()
+}
+
+/// Returns the JSON document carried by a status response.
+pub fn decode_status_response(packet: &RawPacket) -> Result<String, McError> {
+    expect_id(packet, STATUS_RESPONSE_ID)?;
+    let mut body = packet.body.as_slice();
+    read_string(&mut body, packet.body.len())
+}
+
+pub fn encode_ping(payload: i64) -> Vec<u8> {
+    RawPacket::new(PING_ID, payload.to_be_bytes().to_vec()).encode()
+}
+
+pub fn decode_ping(packet: &RawPacket) -> Result<i64, McError> {
+    expect_id(packet, PING_ID)?;
+    read_i64(&packet.body)
+}
+
+pub fn encode_pong(payload: i64) -> Vec<u8> {
+    RawPacket::new(PONG_ID, payload.to_be_bytes().to_vec()).encode()
+}
+
+pub fn decode_pong(packet: &RawPacket) -> Result<i64, McError> {
+    expect_id(packet, PONG_ID)?;
+    read_i64(&packet.body)
+}
+
+/// A LAN world announcement, multicast by the game while a world is opened to LAN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanAnnouncement {
+    pub motd: String,
+    pub port: u16,
+}
+
+impl LanAnnouncement {
+    pub fn encode(&self) -> Vec<u8> {
+        format!("[MOTD]{}[/MOTD][AD]{}[/AD]", self.motd, self.port).into_bytes()
+    }
+
+    pub fn decode(datagram: &[u8]) -> Result<Self, McError> {
+        let text = std::str::from_utf8(datagram)
+            .map_err(|_| McError::InvalidPacket("LAN announcement is not UTF-8".to_string()))?;
+        let motd = between(text, "[MOTD]", "[/MOTD]")
+            .ok_or_else(|| McError::InvalidPacket("Missing [MOTD] tag".to_string()))?;
+        let port = between(text, "[AD]", "[/AD]")
+            .ok_or_else(|| McError::InvalidPacket("Missing [AD] tag".to_string()))?;
+
+        Ok(Self {
+            motd: motd.to_string(),
+            port: port
+                .trim()
+                .parse()
+                .map_err(|_| McError::InvalidPort(port.to_string()))?,
+        })
+    }
+}
+
+fn between<'a>(text: &'a str, open: &str, close: &str) -> Option<&'a str> {
+    let start = text.find(open)? + open.len();
+    let end = start + text[start..].find(close)?;
+    Some(&text[start..end])
+}
+
+/// Builds a [`JavaStatus`] from a status response JSON document.
+pub fn parse_status(json: &Value) -> JavaStatus {
+    let version = JavaVersion {
+        name: json["version"]["name"]
+            .as_str()
+            .unwrap_or("Unknown")
+            .to_string(),
+        protocol: json["version"]["protocol"].as_i64().unwrap_or(0),
+    };
+
+    let players = JavaPlayers {
+        online: json["players"]["online"].as_i64().unwrap_or(0),
+        max: json["players"]["max"].as_i64().unwrap_or(0),
+        sample: json["players"]["sample"].as_array().map(|sample| {
+            sample
+                .iter()
+                .filter_map(|p| {
+                    Some(JavaPlayer {
+                        name: p["name"].as_str()?.to_string(),
+                        id: p["id"].as_str()?.to_string(),
+                    })
+                })
+                .collect()
+        }),
+    };
+
+    let description = if let Some(desc) = json["description"].as_str() {
+        desc.to_string()
+    } else if let Some(text) = json["description"]["text"].as_str() {
+        text.to_string()
+    } else {
+        "No description".to_string()
+    };
+
+    let favicon = json["favicon"].as_str().map(|s| s.to_string());
+    let map = json["map"].as_str().map(|s| s.to_string());
+    let gamemode = json["gamemode"].as_str().map(|s| s.to_string());
+    let software = json["software"].as_str().map(|s| s.to_string());
+
+    let plugins = json["plugins"].as_array().map(|plugins_array| {
+        plugins_array
+            .iter()
+            .filter_map(|p| {
+                Some(JavaPlugin {
+                    name: p["name"].as_str()?.to_string(),
+                    version: p["version"].as_str().map(|s| s.to_string()),
+                })
+            })
+            .collect()
+    });
+
+    let mods = json["mods"].as_array().map(|mods_array| {
+        mods_array
+            .iter()
+            .filter_map(|m| {
+                Some(JavaMod {
+                    modid: m["modid"].as_str()?.to_string(),
+                    version: m["version"].as_str().map(|s| s.to_string()),
+                })
+            })
+            .collect()
+    });
+
+    JavaStatus {
+        version,
+        players,
+        description,
+        favicon,
+        map,
+        gamemode,
+        software,
+        plugins,
+        mods,
+        forge_data: parse_forge_data(json),
+        raw_data: json.clone(),
+    }
+}
+
+fn parse_forge_data(json: &Value) -> Option<ForgeData> {
+    if let Some(forge) = json["forgeData"].as_object() {
+        let channels = forge
+            .get("channels")
+            .and_then(Value::as_array)
+            .map(|channels| {
+                channels
+                    .iter()
+                    .filter_map(|c| {
+                        Some(ForgeChannel {
+                            res: c["res"].as_str()?.to_string(),
+                            version: c["version"].as_str().unwrap_or_default().to_string(),
+                            required: c["required"].as_bool().unwrap_or(false),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        return Some(ForgeData {
+            fml_network_version: forge
+                .get("fmlNetworkVersion")
+                .and_then(Value::as_i64)
+                .unwrap_or(0),
+            channels,
+            mods: parse_mod_list(forge.get("mods"), "modId", "modmarker"),
+        });
+    }
+
+    let modinfo = json["modinfo"].as_object()?;
+    Some(ForgeData {
+        fml_network_version: 0,
+        channels: Vec::new(),
+        mods: parse_mod_list(modinfo.get("modList"), "modid", "version"),
+    })
+}
+
+fn parse_mod_list(list: Option<&Value>, id_key: &str, version_key: &str) -> Vec<JavaMod> {
+    list.and_then(Value::as_array)
+        .map(|mods| {
+            mods.iter()
+                .filter_map(|m| {
+                    Some(JavaMod {
+                        modid: m[id_key].as_str()?.to_string(),
+                        version: m[version_key].as_str().map(|s| s.to_string()),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Builds the status response JSON document for a [`JavaStatus`], the inverse of
+/// [`parse_status`].
+///
+/// Fields of `raw_data` that the model does not cover are kept, and so is a formatted
+/// `description` whose plain text still matches.
+pub fn status_json(status: &JavaStatus) -> Value {
+    let mut json = match &status.raw_data {
+        Value::Object(map) => map.clone(),
+        _ => Map::new(),
+    };
+
+    json.insert(
+        "version".to_string(),
+        json!({ "name": status.version.name, "protocol": status.version.protocol }),
+    );
+
+    let mut players = json!({ "online": status.players.online, "max": status.players.max });
+    if let Some(sample) = &status.players.sample {
+        players["sample"] = json!(sample);
+    }
+    json.insert("players".to_string(), players);
+
+    let raw_description = parse_status(&status.raw_data).description;
+    if !json.contains_key("description") || raw_description != status.description {
+        json.insert("description".to_string(), json!(status.description));
+    }
+
+    set_optional(&mut json, "favicon", &status.favicon);
+    set_optional(&mut json, "map", &status.map);
+    set_optional(&mut json, "gamemode", &status.gamemode);
+    set_optional(&mut json, "software", &status.software);
+    set_optional(&mut json, "plugins", &status.plugins);
+    set_optional(&mut json, "mods", &status.mods);
+
+    json.remove("forgeData");
+    json.remove("modinfo");
+    if let Some(forge) = &status.forge_data {
+        if forge.fml_network_version == 0 {
+            let mods: Vec<Value> = forge
+                .mods
+                .iter()
+                .map(|m| json!({ "modid": m.modid, "version": m.version }))
+                .collect();
+            json.insert(
+                "modinfo".to_string(),
+                json!({ "type": "FML", "modList": mods }),
+            );
+        } else {
+            let mods: Vec<Value> = forge
+                .mods
+                .iter()
+                .map(|m| json!({ "modId": m.modid, "modmarker": m.version }))
+                .collect();
+            json.insert(
+                "forgeData".to_string(),
+                json!({
+                    "channels": forge.channels,
+                    "mods": mods,
+                    "fmlNetworkVersion": forge.fml_network_version,
+                }),
+            );
+        }
+    }
+
+    Value::Object(json)
+}
+
+fn set_optional<T: serde::Serialize>(json: &mut Map<String, Value>, key: &str, value: &Option<T>) {
+    match value {
+        Some(value) => {
+            json.insert(key.to_string(), json!(value));
+        }
+        None => {
+            json.remove(key);
+        }
+    }
+}
+
+fn expect_id(packet: &RawPacket, id: i32) -> Result<(), McError> {
+    if packet.id != id {
+        return Err(McError::UnexpectedPacketId(packet.id));
+    }
+    Ok(())
+}
+
+fn read_i64(body: &[u8]) -> Result<i64, McError> {
+    let bytes: [u8; 8] = body
+        .get(..8)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| McError::InvalidPacket("Truncated ping payload".to_string()))?;
+    Ok(i64::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{FrameDecoder, write_var_int};
+
+    const JSON: &str = r#"{"description":"A Minecraft Server"}"#;
+
+    fn decode_frame(bytes: &[u8], limit: usize) -> Result<Option<RawPacket>, McError> {
+        let mut decoder = FrameDecoder::new(limit);
+        decoder.push(bytes);
+        decoder.next_packet()
+    }
+
+    fn response_with_body(body: Vec<u8>) -> RawPacket {
+        RawPacket::new(STATUS_RESPONSE_ID, body)
+    }
+
+    #[test]
+    fn status_response_round_trips_within_limit() {
+        let bytes = encode_status_response(JSON);
+        let packet = decode_frame(&bytes, bytes.len()).unwrap().unwrap();
+        assert_eq!(decode_status_response(&packet).unwrap(), JSON);
+    }
+
+    #[test]
+    fn response_over_limit_is_rejected() {
+        let bytes = encode_status_response(&"x".repeat(4096));
+        assert!(matches!(
+            decode_frame(&bytes, 1024),
+            Err(McError::ResponseTooLarge { limit: 1024, size }) if size > 4096
+        ));
+    }
+
+    #[test]
+    fn overlong_length_prefix_is_rejected() {
+        assert!(matches!(
+            decode_frame(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01], 1024),
+            Err(McError::InvalidVarInt)
+        ));
+    }
+
+    #[test]
+    fn negative_frame_length_is_rejected() {
+        let mut bytes = Vec::new();
+        write_var_int(&mut bytes, -1);
+        assert!(matches!(
+            decode_frame(&bytes, 1024),
+            Err(McError::InvalidLength(-1))
+        ));
+    }
+
+    #[test]
+    fn malformed_json_string_lengths_are_rejected() {
+        let mut negative = Vec::new();
+        write_var_int(&mut negative, -5);
+        assert!(matches!(
+            decode_status_response(&response_with_body(negative)),
+            Err(McError::InvalidLength(-5))
+        ));
+
+        // Announces more bytes than the packet carries.
+        let mut long = Vec::new();
+        write_var_int(&mut long, 100);
+        long.extend_from_slice(b"short");
+        assert!(matches!(
+            decode_status_response(&response_with_body(long)),
+            Err(McError::ResponseTooLarge { size: 100, .. })
+        ));
+    }
+
+    #[test]
+    fn wrong_packet_id_is_rejected() {
+        let packet = RawPacket::new(PONG_ID, Vec::new());
+        assert!(matches!(
+            decode_status_response(&packet),
+            Err(McError::UnexpectedPacketId(id)) if id == PONG_ID
+        ));
+    }
+}
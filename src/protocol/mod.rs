@@ -0,0 +1,253 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Sans-IO encoders and decoders for the packets this crate speaks.
+//!
+//! Nothing in this module touches a socket: encoders return byte buffers and decoders take
+//! byte slices, so they can be reused in proxies, servers and tests.
+
+pub mod bedrock;
+pub mod java;
+
+use std::io::Read;
+
+use crate::error::McError;
+
+/// Appends `value` to `buffer` as a Minecraft VarInt.
+pub fn write_var_int(buffer: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        let mut temp = (value & 0x7F) as u8;
+        value >>= 7;
+        if value != 0 {
+            temp |= 0x80;
+        }
+        buffer.push(temp);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+/// Appends `s` to `buffer` as a VarInt-prefixed UTF-8 string.
+pub fn write_string(buffer: &mut Vec<u8>, s: &str) {
+    write_var_int(buffer, s.len() as i32);
+    buffer.extend_from_slice(s.as_bytes());
+}
+
+pub fn read_var_int(reader: &mut impl Read) -> Result<i32, McError> {
+    let mut result = 0i32;
+    for i in 0..5 {
+        let mut byte = [0u8];
+        reader
+            .read_exact(&mut byte)
+            .map_err(|_| McError::InvalidPacket("Truncated VarInt".to_string()))?;
+        result |= ((byte[0] & 0x7F) as i32) << (7 * i);
+        if (byte[0] & 0x80) == 0 {
+            return Ok(result);
+        }
+    }
+    Err(McError::InvalidVarInt)
+}
+
+/// Reads a VarInt-prefixed UTF-8 string of at most `max_len` bytes.
+pub fn read_string(reader: &mut impl Read, max_len: usize) -> Result<String, McError> {
+    let len = checked_length(read_var_int(reader)?, max_len)?;
+    let mut bytes = vec![0u8; len];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|_| McError::InvalidPacket("Truncated string".to_string()))?;
+    String::from_utf8(bytes).map_err(McError::Utf8Error)
+}
+
+/// Decodes a VarInt at the start of `buf`, returning its value and encoded size, or
+/// `None` if `buf` ends before the VarInt does.
+pub fn peek_var_int(buf: &[u8]) -> Result<Option<(i32, usize)>, McError> {
+    let mut result = 0i32;
+    for (i, byte) in buf.iter().take(5).enumerate() {
+        result |= ((byte & 0x7F) as i32) << (7 * i);
+        if (byte & 0x80) == 0 {
+            return Ok(Some((result, i + 1)));
+        }
+    }
+    if buf.len() >= 5 {
+        Err(McError::InvalidVarInt)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Validates a length announced by the peer against `limit`.
+pub fn checked_length(length: i32, limit: usize) -> Result<usize, McError> {
+    let length = usize::try_from(length).map_err(|_| McError::InvalidLength(length))?;
+    if length > limit {
+        return Err(McError::ResponseTooLarge {
+            size: length,
+            limit,
+        });
+    }
+    Ok(length)
+}
+
+/// A length-prefixed Java packet with its ID split off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPacket {
+    pub id: i32,
+    pub body: Vec<u8>,
+}
+
+impl RawPacket {
+    pub fn new(id: i32, body: Vec<u8>) -> Self {
+        Self { id, body }
+    }
+
+    /// Encodes the packet with its length prefix, ready to be written to a stream.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(self.body.len() + 5);
+        write_var_int(&mut payload, self.id);
+        payload.extend_from_slice(&self.body);
+
+        let mut packet = Vec::with_capacity(payload.len() + 5);
+        write_var_int(&mut packet, payload.len() as i32);
+        packet.extend_from_slice(&payload);
+        packet
+    }
+}
+
+/// Incrementally splits a byte stream into length-prefixed Java packets.
+///
+/// Feed received bytes with [`push`](Self::push) and drain complete packets with
+/// [`next_packet`](Self::next_packet). Packets announced as longer than `max_len` are
+/// rejected before their body is buffered.
+#[derive(Debug, Clone)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_len: usize,
+}
+
+impl FrameDecoder {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_len,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Number of buffered bytes that do not yet form a complete packet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn next_packet(&mut self) -> Result<Option<RawPacket>, McError> {
+        let Some((length, header_len)) = peek_var_int(&self.buffer)? else {
+            return Ok(None);
+        };
+        let length = checked_length(length, self.max_len)?;
+        if self.buffer.len() < header_len + length {
+            return Ok(None);
+        }
+
+        let frame: Vec<u8> = self.buffer.drain(..header_len + length).collect();
+        let mut body = &frame[header_len..];
+        let id = read_var_int(&mut body)?;
+        Ok(Some(RawPacket::new(id, body.to_vec())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn var_int_round_trips() {
+        for value in [0, 1, 127, 128, 255, 25565, i32::MAX, -1, i32::MIN] {
+            let mut buf = Vec::new();
+            write_var_int(&mut buf, value);
+            assert_eq!(read_var_int(&mut buf.as_slice()).unwrap(), value);
+            assert_eq!(peek_var_int(&buf).unwrap(), Some((value, buf.len())));
+        }
+    }
+
+    #[test]
+    fn var_int_rejects_overlong_and_truncated_input() {
+        let overlong = [0x80u8; 6];
+        assert!(matches!(
+            read_var_int(&mut overlong.as_slice()),
+            Err(McError::InvalidVarInt)
+        ));
+        assert!(matches!(
+            peek_var_int(&overlong),
+            Err(McError::InvalidVarInt)
+        ));
+
+        let truncated = [0x80u8, 0x80];
+        assert!(matches!(
+            read_var_int(&mut truncated.as_slice()),
+            Err(McError::InvalidPacket(_))
+        ));
+        assert_eq!(peek_var_int(&truncated).unwrap(), None);
+    }
+
+    #[test]
+    fn checked_length_enforces_limit() {
+        assert_eq!(checked_length(10, 10).unwrap(), 10);
+        assert!(matches!(
+            checked_length(11, 10),
+            Err(McError::ResponseTooLarge {
+                size: 11,
+                limit: 10
+            })
+        ));
+        assert!(matches!(
+            checked_length(-1, 10),
+            Err(McError::InvalidLength(-1))
+        ));
+    }
+
+    #[test]
+    fn frame_decoder_reassembles_split_frames() {
+        let first = RawPacket::new(0x00, vec![b'x'; 300]);
+        let second = RawPacket::new(0x01, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        let mut bytes = first.encode();
+        bytes.extend(second.encode());
+
+        let mut decoder = FrameDecoder::new(1024);
+        let mut packets = Vec::new();
+        // One byte at a time splits both the length prefix and the body.
+        for byte in &bytes {
+            decoder.push(std::slice::from_ref(byte));
+            while let Some(packet) = decoder.next_packet().unwrap() {
+                packets.push(packet);
+            }
+        }
+        assert_eq!(packets, vec![first, second]);
+        assert_eq!(decoder.buffered(), 0);
+    }
+
+    #[test]
+    fn frame_decoder_rejects_oversized_frame_before_body() {
+        let packet = RawPacket::new(0x00, vec![0; 100]).encode();
+        let mut decoder = FrameDecoder::new(64);
+        decoder.push(&packet[..2]);
+        assert!(matches!(
+            decoder.next_packet(),
+            Err(McError::ResponseTooLarge {
+                size: 101,
+                limit: 64
+            })
+        ));
+    }
+
+    #[test]
+    fn frame_decoder_rejects_empty_frame() {
+        let mut decoder = FrameDecoder::new(64);
+        decoder.push(&[0x00]);
+        assert!(matches!(
+            decoder.next_packet(),
+            Err(McError::InvalidPacket(_))
+        ));
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Availability reports computed from stored ping history.
+
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ErrorRecord, McError};
+use crate::history::{HistoryRecord, HistoryStore};
+
+/// The last 24 hours, 7 days and 30 days.
+pub const DEFAULT_WINDOWS: [Duration; 3] = [
+    Duration::from_secs(24 * 60 * 60),
+    Duration::from_secs(7 * 24 * 60 * 60),
+    Duration::from_secs(30 * 24 * 60 * 60),
+];
+
+/// A ping describes the server for at most this many median ping intervals.
+const MAX_GAP_FACTOR: u32 = 3;
+
+/// Availability of one server over several windows ending at the same time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UptimeReport {
+    pub server: String,
+    pub generated_at: SystemTime,
+    pub windows: Vec<WindowReport>,
+}
+
+/// Availability over one window.
+///
+/// Each ping is taken to describe the server until the next one, so uptime is weighted by
+/// time rather than by ping count. A ping covers at most three times the median interval
+/// between pings; the rest of a longer gap, time after the last ping and time before the
+/// first are unobserved and left out of both uptime and outage durations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowReport {
+    pub window: Duration,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub samples: usize,
+    /// Percentage of the observed time the server was online, `None` without samples.
+    pub uptime: Option<f64>,
+    pub outages: Vec<Outage>,
+    pub latency: Option<LatencyStats>,
+    pub player_peak: Option<PlayerPeak>,
+}
+
+/// A run of failed pings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outage {
+    pub start: SystemTime,
+    /// When the server answered again, `None` if it is still down.
+    pub end: Option<SystemTime>,
+    /// Time the server was observed down, which leaves out gaps in the history.
+    pub duration: Duration,
+    /// The error of the first failed ping.
+    pub reason: Option<ErrorRecord>,
+}
+
+/// Latency of successful pings in ms.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub mean: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerPeak {
+    pub online: i64,
+    pub at: SystemTime,
+}
+
+impl UptimeReport {
+    /// Reports on `server` over the [`DEFAULT_WINDOWS`], ending now.
+    pub async fn generate(store: &dyn HistoryStore, server: &str) -> Result<Self, McError> {
+        Self::generate_for(store, server, &DEFAULT_WINDOWS, SystemTime::now()).await
+    }
+
+    /// Reports on `server` over `windows`, each ending at `end`.
+    pub async fn generate_for(
+        store: &dyn HistoryStore,
+        server: &str,
+        windows: &[Duration],
+        end: SystemTime,
+    ) -> Result<Self, McError> {
+        let longest = windows.iter().max().copied().unwrap_or_default();
+        let start = end.checked_sub(longest).unwrap_or(SystemTime::UNIX_EPOCH);
+        let records = store.query(server, start..end).await?;
+        Ok(Self::from_records(server, &records, windows, end))
+    }
+
+    /// Builds a report from records sorted oldest first.
+    pub fn from_records(
+        server: &str,
+        records: &[HistoryRecord],
+        windows: &[Duration],
+        end: SystemTime,
+    ) -> Self {
+        Self {
+            server: server.to_string(),
+            generated_at: end,
+            windows: windows
+                .iter()
+                .map(|window| WindowReport::from_records(records, *window, end))
+                .collect(),
+        }
+    }
+}
+
+impl WindowReport {
+    /// Summarizes the `records` that fall within `window` before `end`.
+    pub fn from_records(records: &[HistoryRecord], window: Duration, end: SystemTime) -> Self {
+        let start = end.checked_sub(window).unwrap_or(SystemTime::UNIX_EPOCH);
+        let records: Vec<_> = records
+            .iter()
+            .filter(|record| record.at >= start && record.at < end)
+            .collect();
+
+        let mut intervals: Vec<Duration> = records
+            .windows(2)
+            .map(|pair| pair[1].at.duration_since(pair[0].at).unwrap_or_default())
+            .collect();
+        intervals.sort();
+        let max_gap = intervals
+            .get(intervals.len() / 2)
+            .map_or(Duration::ZERO, |median| *median * MAX_GAP_FACTOR);
+
+        let mut online_time = Duration::ZERO;
+        let mut observed = Duration::ZERO;
+        let mut outages: Vec<Outage> = Vec::new();
+        for (i, record) in records.iter().enumerate() {
+            let until = records.get(i + 1).map_or(end, |next| next.at);
+            let span = until
+                .duration_since(record.at)
+                .unwrap_or_default()
+                .min(max_gap);
+            observed += span;
+
+            let ongoing = outages.last_mut().filter(|outage| outage.end.is_none());
+            match (record.online, ongoing) {
+                (true, Some(outage)) => outage.end = Some(record.at),
+                (true, None) => {}
+                (false, Some(outage)) => outage.duration += span,
+                (false, None) => outages.push(Outage {
+                    start: record.at,
+                    end: None,
+                    duration: span,
+                    reason: record.error.clone(),
+                }),
+            }
+            if record.online {
+                online_time += span;
+            }
+        }
+
+        let uptime = match (records.is_empty(), observed.is_zero()) {
+            (true, _) => None,
+            // A single ping at the very end of the window.
+            (false, true) => Some(if records[0].online { 100.0 } else { 0.0 }),
+            (false, false) => Some(online_time.as_secs_f64() / observed.as_secs_f64() * 100.0),
+        };
+
+        let mut latencies: Vec<f64> = records.iter().filter_map(|r| r.latency).collect();
+        latencies.sort_by(f64::total_cmp);
+        let latency = (!latencies.is_empty()).then(|| LatencyStats {
+            mean: latencies.iter().sum::<f64>() / latencies.len() as f64,
+            p95: percentile(&latencies, 95.0),
+            p99: percentile(&latencies, 99.0),
+        });
+
+        let player_peak = records
+            .iter()
+            .filter_map(|record| {
+                record.players_online.map(|online| PlayerPeak {
+                    online,
+                    at: record.at,
+                })
+            })
+            .reduce(|peak, next| {
+                if next.online > peak.online {
+                    next
+                } else {
+                    peak
+                }
+            });
+
+        Self {
+            window,
+            start,
+            end,
+            samples: records.len(),
+            uptime,
+            outages,
+            latency,
+            player_peak,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`.
+fn percentile(values: &[f64], percent: f64) -> f64 {
+    let rank = (percent / 100.0 * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ServerEdition;
+    use std::time::UNIX_EPOCH;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + DAY + Duration::from_secs(secs)
+    }
+
+    fn ping(secs: u64, online: bool) -> HistoryRecord {
+        HistoryRecord {
+            server: "mc.test".to_string(),
+            edition: ServerEdition::Java,
+            at: at(secs),
+            online,
+            latency: online.then_some(20.0),
+            players_online: online.then_some(secs as i64 % 7),
+            players_max: online.then_some(20),
+            version: None,
+            error: (!online)
+                .then(|| McError::InvalidAddress(format!("down at {secs}")).to_record()),
+        }
+    }
+
+    fn pings(online: bool, times: impl IntoIterator<Item = u64>) -> Vec<HistoryRecord> {
+        times.into_iter().map(|secs| ping(secs, online)).collect()
+    }
+
+    #[test]
+    fn empty_history_has_no_uptime() {
+        let report = WindowReport::from_records(&[], DAY, at(0));
+
+        assert_eq!(report.samples, 0);
+        assert_eq!(report.uptime, None);
+        assert!(report.outages.is_empty());
+        assert_eq!(report.latency, None);
+        assert_eq!(report.player_peak, None);
+    }
+
+    #[test]
+    fn long_gaps_are_left_unobserved() {
+        // Minutely pings, then three hours without any.
+        let mut records = pings(true, [0, 60, 120, 180]);
+        records.extend(pings(false, [10_980, 11_040]));
+        records.extend(pings(true, [11_100]));
+        let report = WindowReport::from_records(&records, DAY, at(11_160));
+
+        // The ping before the gap covers three minutes of it, not all three hours.
+        let uptime = report.uptime.unwrap();
+        assert!((uptime - 420.0 / 540.0 * 100.0).abs() < 1e-9, "{uptime}");
+        assert_eq!(report.samples, 7);
+    }
+
+    #[test]
+    fn outage_across_a_gap_counts_observed_time() {
+        let mut records = pings(true, [0, 60, 120]);
+        records.extend(pings(false, [180, 240, 20_000]));
+        records.extend(pings(true, [20_060, 20_120]));
+        let report = WindowReport::from_records(&records, DAY, at(20_180));
+
+        let [outage] = report.outages.as_slice() else {
+            panic!("expected one outage, got {:?}", report.outages);
+        };
+        assert_eq!(outage.start, at(180));
+        assert_eq!(outage.end, Some(at(20_060)));
+        assert_eq!(outage.duration, Duration::from_secs(60 + 180 + 60));
+        assert_eq!(
+            outage.reason.as_ref().map(|reason| reason.message.as_str()),
+            Some("Invalid address format: down at 180")
+        );
+    }
+
+    #[test]
+    fn windows_only_see_their_own_records() {
+        let records = pings(true, (0..48).map(|hour| hour * HOUR.as_secs()));
+        let report =
+            UptimeReport::from_records("mc.test", &records, &[HOUR, DAY], at(48 * HOUR.as_secs()));
+
+        assert_eq!(report.windows[0].samples, 1);
+        assert_eq!(report.windows[1].samples, 24);
+        assert_eq!(report.windows[1].uptime, Some(100.0));
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::time::Duration;
+
+use crate::error::{ErrorKind, McError};
+use crate::random::random_unit;
+
+/// Controls how many times a ping is attempted and how long to wait between attempts.
+///
+/// The delay before retry `n` (1-based) is `initial_backoff * multiplier^(n - 1)`, capped at
+/// `max_backoff`, then reduced by a random fraction of up to `jitter` so that many clients
+/// retrying at once do not hit a server in lockstep.
+///
+/// By default only errors that report [`McError::is_retryable`] are retried; `retry_on`
+/// replaces that with an explicit list of error kinds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    pub retry_on: Option<Vec<ErrorKind>>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: 0.5,
+            retry_on: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// A policy that makes a single attempt and never retries.
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn retry_on(mut self, kinds: &[ErrorKind]) -> Self {
+        self.retry_on = Some(kinds.to_vec());
+        self
+    }
+
+    pub fn should_retry(&self, error: &McError, attempt: u32) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        match &self.retry_on {
+            Some(kinds) => kinds.contains(&error.kind()),
+            None => error.is_retryable(),
+        }
+    }
+
+    /// Delay to wait after the given failed attempt (1-based) before trying again.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let capped = base.min(self.max_backoff.as_secs_f64());
+        let jittered = capped * (1.0 - self.jitter * random_unit());
+        Duration::from_secs_f64(jittered.max(0.0))
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Scanning address ranges for servers.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::RangeInclusive;
+
+pub use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+
+use crate::error::McError;
+use crate::models::{ServerEdition, ServerInfo};
+use crate::random::random_u64;
+
+/// A set of scan targets: every address of some networks, on some ports, for some
+/// editions.
+///
+/// Targets are generated lazily from their index, so huge ranges cost no memory. By
+/// default they come in a random order, so consecutive pings hit different hosts; a fixed
+/// [`seed`](Self::with_seed) makes the order reproducible.
+#[derive(Debug, Clone)]
+pub struct Scan {
+    networks: Vec<IpNet>,
+    exclude: Vec<IpNet>,
+    ports: Vec<RangeInclusive<u16>>,
+    editions: Vec<ServerEdition>,
+    order: ScanOrder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanOrder {
+    Sequential,
+    Shuffled { seed: u64 },
+}
+
+impl Default for Scan {
+    fn default() -> Self {
+        Self {
+            networks: Vec::new(),
+            exclude: Vec::new(),
+            ports: Vec::new(),
+            editions: vec![ServerEdition::Java],
+            order: ScanOrder::Shuffled { seed: random_u64() },
+        }
+    }
+}
+
+impl Scan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_network(mut self, network: IpNet) -> Self {
+        self.networks.push(network);
+        self
+    }
+
+    /// Adds a network in CIDR notation, e.g. `10.0.0.0/24`. A bare address is a single host.
+    pub fn with_cidr(self, cidr: &str) -> Result<Self, McError> {
+        let network = cidr
+            .parse::<IpNet>()
+            .or_else(|_| cidr.parse::<IpAddr>().map(IpNet::from))
+            .map_err(|_| McError::InvalidAddress(cidr.to_string()))?;
+        Ok(self.with_network(network))
+    }
+
+    /// Never ping addresses in `network`.
+    pub fn with_exclude(mut self, network: IpNet) -> Self {
+        self.exclude.push(network);
+        self
+    }
+
+    /// Ping these ports. Without any, each edition's default port is used.
+    pub fn with_ports(mut self, ports: RangeInclusive<u16>) -> Self {
+        self.ports.push(ports);
+        self
+    }
+
+    pub fn with_editions(mut self, editions: &[ServerEdition]) -> Self {
+        self.editions = editions.to_vec();
+        self
+    }
+
+    /// Shuffle the targets reproducibly.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.order = ScanOrder::Shuffled { seed };
+        self
+    }
+
+    /// Scan targets in address order.
+    pub fn sequential(mut self) -> Self {
+        self.order = ScanOrder::Sequential;
+        self
+    }
+
+    pub fn with_order(mut self, order: ScanOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn order(&self) -> ScanOrder {
+        self.order
+    }
+
+    /// Identifies the target set, ignoring order, so a checkpoint is only resumed against
+    /// the scan that wrote it.
+    pub(crate) fn fingerprint(&self) -> String {
+        format!(
+            "{:?} {:?} {:?} {:?}",
+            self.networks, self.exclude, self.ports, self.editions
+        )
+    }
+
+    /// Number of targets before exclusions are applied.
+    pub fn len(&self) -> Result<u64, McError> {
+        let hosts = self
+            .networks
+            .iter()
+            .try_fold(0u64, |total, net| total.checked_add(network_size(net)?))
+            .ok_or_else(too_large)?;
+        hosts
+            .checked_mul(self.port_count())
+            .and_then(|n| n.checked_mul(self.editions.len() as u64))
+            .ok_or_else(too_large)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, McError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// The targets in scan order, skipping excluded addresses.
+    pub fn targets(&self) -> Result<ScanTargets, McError> {
+        let len = self.len()?;
+        let permutation = match self.order {
+            ScanOrder::Sequential => Permutation::identity(len),
+            ScanOrder::Shuffled { seed } => Permutation::shuffled(len, seed),
+        };
+        Ok(ScanTargets {
+            scan: self.clone(),
+            permutation,
+            position: 0,
+        })
+    }
+
+    /// The target at `index` in address order.
+    fn target(&self, mut index: u64) -> Option<ServerInfo> {
+        let edition = self.editions[(index % self.editions.len() as u64) as usize];
+        index /= self.editions.len() as u64;
+
+        let port_count = self.port_count();
+        let port = if self.ports.is_empty() {
+            default_port(edition)
+        } else {
+            nth_port(&self.ports, index % port_count)
+        };
+        index /= port_count;
+
+        let ip = self.networks.iter().find_map(|net| {
+            let size = network_size(net).unwrap_or(u64::MAX);
+            if index < size {
+                Some(nth_address(net, index))
+            } else {
+                index -= size;
+                None
+            }
+        })?;
+
+        if self.exclude.iter().any(|net| net.contains(&ip)) {
+            return None;
+        }
+        Some(ServerInfo::new(
+            SocketAddr::new(ip, port).to_string(),
+            edition,
+        ))
+    }
+
+    fn port_count(&self) -> u64 {
+        if self.ports.is_empty() {
+            1
+        } else {
+            self.ports.iter().map(|r| r.clone().count() as u64).sum()
+        }
+    }
+}
+
+/// Lazily yields the targets of a [`Scan`].
+#[derive(Debug, Clone)]
+pub struct ScanTargets {
+    scan: Scan,
+    permutation: Permutation,
+    position: u64,
+}
+
+impl ScanTargets {
+    /// Number of target slots consumed so far, including excluded ones.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Continues from slot `position`, as returned by [`position`](Self::position).
+    pub fn skip_to(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Like [`next`](Iterator::next), also returning the slot the target came from.
+    pub fn next_indexed(&mut self) -> Option<(u64, ServerInfo)> {
+        while self.position < self.permutation.len {
+            let slot = self.position;
+            self.position += 1;
+            if let Some(target) = self.scan.target(self.permutation.apply(slot)) {
+                return Some((slot, target));
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for ScanTargets {
+    type Item = ServerInfo;
+
+    fn next(&mut self) -> Option<ServerInfo> {
+        self.next_indexed().map(|(_, target)| target)
+    }
+}
+
+/// A bijection on `0..len` of the form `(a * i + b) mod len`, with `a` coprime to `len`.
+#[derive(Debug, Clone, Copy)]
+struct Permutation {
+    len: u64,
+    multiplier: u64,
+    offset: u64,
+}
+
+impl Permutation {
+    fn identity(len: u64) -> Self {
+        Self {
+            len,
+            multiplier: 1,
+            offset: 0,
+        }
+    }
+
+    fn shuffled(len: u64, seed: u64) -> Self {
+        if len < 2 {
+            return Self::identity(len);
+        }
+
+        let mixed = splitmix64(seed);
+        // Start near the golden ratio of `len` so neighbours land far apart.
+        let mut multiplier = ((len as f64 * 0.618_033_988_75) as u64 + mixed % 1024) % len;
+        while multiplier == 0 || gcd(multiplier, len) != 1 {
+            multiplier = (multiplier + 1) % len;
+        }
+
+        Self {
+            len,
+            multiplier,
+            offset: splitmix64(mixed) % len,
+        }
+    }
+
+    fn apply(&self, i: u64) -> u64 {
+        ((self.multiplier as u128 * i as u128 + self.offset as u128) % self.len as u128) as u64
+    }
+}
+
+fn network_size(net: &IpNet) -> Option<u64> {
+    let host_bits = u32::from(net.max_prefix_len() - net.prefix_len());
+    1u64.checked_shl(host_bits)
+}
+
+fn nth_address(net: &IpNet, index: u64) -> IpAddr {
+    match net.network() {
+        IpAddr::V4(base) => IpAddr::V4(Ipv4Addr::from(u32::from(base) + index as u32)),
+        IpAddr::V6(base) => IpAddr::V6(Ipv6Addr::from(u128::from(base) + index as u128)),
+    }
+}
+
+fn nth_port(ranges: &[RangeInclusive<u16>], mut index: u64) -> u16 {
+    for range in ranges {
+        let size = range.clone().count() as u64;
+        if index < size {
+            return range.start() + index as u16;
+        }
+        index -= size;
+    }
+    unreachable!("port index out of range")
+}
+
+fn default_port(edition: ServerEdition) -> u16 {
+    match edition {
+        ServerEdition::Java => 25565,
+        ServerEdition::Bedrock => 19132,
+    }
+}
+
+fn too_large() -> McError {
+    McError::InvalidAddress("scan has more than 2^64 targets".to_string())
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn shuffled_order_visits_every_slot_once() {
+        for len in [2, 3, 10, 97, 256, 1000, 4096] {
+            for seed in [0, 1, 42, u64::MAX] {
+                let permutation = Permutation::shuffled(len, seed);
+                let visited: HashSet<u64> = (0..len).map(|i| permutation.apply(i)).collect();
+                assert_eq!(visited.len() as u64, len, "len {len}, seed {seed}");
+                assert!(visited.iter().all(|index| *index < len));
+            }
+        }
+    }
+
+    #[test]
+    fn scan_yields_every_address_once() {
+        let scan = Scan::new()
+            .with_cidr("10.0.0.0/24")
+            .unwrap()
+            .with_ports(25565..=25566)
+            .with_seed(7);
+        let targets: Vec<String> = scan.targets().unwrap().map(|t| t.address).collect();
+        let unique: HashSet<&String> = targets.iter().collect();
+
+        assert_eq!(targets.len(), 512);
+        assert_eq!(unique.len(), 512);
+        assert!(unique.contains(&"10.0.0.255:25566".to_string()));
+        // Shuffled, not in address order.
+        let sequential: Vec<String> = scan
+            .sequential()
+            .targets()
+            .unwrap()
+            .map(|t| t.address)
+            .collect();
+        assert_ne!(targets, sequential);
+    }
+
+    #[test]
+    fn exclusions_and_resume_position_are_respected() {
+        let scan = Scan::new()
+            .with_cidr("192.168.1.0/28")
+            .unwrap()
+            .with_exclude("192.168.1.0/30".parse().unwrap())
+            .with_seed(3);
+        let mut first = scan.targets().unwrap();
+        let head: Vec<ServerInfo> = first.by_ref().take(4).collect();
+        let mut resumed = scan.targets().unwrap();
+        resumed.skip_to(first.position());
+
+        let addresses: HashSet<String> =
+            head.into_iter().chain(resumed).map(|t| t.address).collect();
+        assert_eq!(addresses.len(), 12);
+        assert!(!addresses.contains("192.168.1.2:25565"));
+    }
+}
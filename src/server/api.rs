@@ -0,0 +1,371 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::time::Instant;
+
+use super::http::{Request, Response, read_request, write_response};
+use crate::client::McClient;
+use crate::error::{ErrorKind, ErrorRecord};
+use crate::models::{ServerData, ServerEdition, ServerStatus};
+
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(30);
+const DEFAULT_RATE: f64 = 5.0;
+const DEFAULT_BURST: u32 = 20;
+/// Most servers accepted in one batch request.
+const MAX_BATCH: usize = 100;
+/// Expired cache entries and idle rate limit buckets are swept once a map grows past this.
+const SWEEP_THRESHOLD: usize = 4096;
+const JSON: &str = "application/json";
+
+/// An HTTP service that serves server statuses as JSON.
+///
+/// * `GET /status/java/{address}` and `GET /status/bedrock/{address}` return the
+///   [`ServerStatus`], or `502` with `{"error": ErrorRecord}` if the ping failed.
+/// * `GET /icon/{address}.png` returns a Java server's favicon.
+/// * `POST /status/batch` takes `[{"address": "...", "edition": "java"}, ...]` and returns
+///   one `{"address", "edition", "status"}` or `{"address", "edition", "error"}` per entry,
+///   in order.
+///
+/// Results, failures included, are cached per server for the cache TTL. Each client IP
+/// gets a token bucket; a batch costs one token per server. CORS headers allow any origin
+/// unless origins are listed with [`with_cors_origin`](Self::with_cors_origin).
+pub struct StatusApi {
+    listener: TcpListener,
+    client: McClient,
+    cache_ttl: Duration,
+    rate: f64,
+    burst: u32,
+    cors_origins: Vec<String>,
+}
+
+struct ApiState {
+    api: StatusApi,
+    cache: Mutex<HashMap<(ServerEdition, String), CacheEntry>>,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+struct CacheEntry {
+    fetched_at: Instant,
+    result: Result<ServerStatus, ErrorRecord>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct BatchItem {
+    address: String,
+    edition: String,
+}
+
+#[derive(Serialize)]
+struct BatchEntry {
+    address: String,
+    edition: ServerEdition,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<ServerStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorRecord>,
+}
+
+impl StatusApi {
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            client: McClient::new(),
+            cache_ttl: DEFAULT_CACHE_TTL,
+            rate: DEFAULT_RATE,
+            burst: DEFAULT_BURST,
+            cors_origins: Vec::new(),
+        })
+    }
+
+    pub fn with_client(mut self, client: McClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// How long a result is served from cache before pinging again.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Requests per second each client IP may make on average, and how many it may make
+    /// at once.
+    pub fn with_rate_limit(mut self, per_second: f64, burst: u32) -> Self {
+        self.rate = per_second;
+        self.burst = burst;
+        self
+    }
+
+    /// Allow browser requests from `origin`, e.g. `https://example.com`.
+    pub fn with_cors_origin(mut self, origin: impl Into<String>) -> Self {
+        self.cors_origins.push(origin.into());
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves the JSON API until accepting a connection fails. Cached statuses and
+    /// per-client rate-limit buckets are kept for as long as this call runs.
+    pub async fn run(self) -> io::Result<()> {
+        let state = Arc::new(ApiState {
+            api: self,
+            cache: Mutex::new(HashMap::new()),
+            buckets: Mutex::new(HashMap::new()),
+        });
+
+        loop {
+            let (stream, peer) = state.api.listener.accept().await?;
+            let state = state.clone();
+            tokio::spawn(async move {
+                let _ = state.serve_connection(stream, peer.ip()).await;
+            });
+        }
+    }
+}
+
+impl ApiState {
+    async fn serve_connection(&self, mut stream: TcpStream, peer: IpAddr) -> io::Result<()> {
+        let Some(request) = read_request(&mut stream).await? else {
+            return Ok(());
+        };
+        let response = self.respond(&request, peer).await;
+        let response = self.cors(&request, response);
+        write_response(&mut stream, response).await
+    }
+
+    async fn respond(&self, request: &Request, peer: IpAddr) -> Response {
+        if request.method == "OPTIONS" {
+            return Response::new(204, JSON, Vec::new())
+                .with_header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
+                .with_header("Access-Control-Allow-Headers", "Content-Type")
+                .with_header("Access-Control-Max-Age", "86400");
+        }
+
+        let path = request.path.as_str();
+        match (request.method.as_str(), path) {
+            ("POST", "/status/batch") => self.batch(request, peer).await,
+            ("GET", _) => {
+                if let Some(address) = path.strip_prefix("/status/java/") {
+                    self.single(ServerEdition::Java, address, peer).await
+                } else if let Some(address) = path.strip_prefix("/status/bedrock/") {
+                    self.single(ServerEdition::Bedrock, address, peer).await
+                } else if let Some(address) = path
+                    .strip_prefix("/icon/")
+                    .and_then(|rest| rest.strip_suffix(".png"))
+                {
+                    self.icon(address, peer).await
+                } else {
+                    error_response(404, "not found")
+                }
+            }
+            _ => error_response(405, "method not allowed"),
+        }
+    }
+
+    async fn single(&self, edition: ServerEdition, address: &str, peer: IpAddr) -> Response {
+        if let Err(response) = self.admit(peer, 1) {
+            return response;
+        }
+        let (result, age) = self.lookup(edition, address).await;
+        let response = match result {
+            Ok(status) => json_response(200, &status),
+            Err(error) => error_json(&error),
+        };
+        self.cache_headers(response, age)
+    }
+
+    async fn icon(&self, address: &str, peer: IpAddr) -> Response {
+        if let Err(response) = self.admit(peer, 1) {
+            return response;
+        }
+        let (result, age) = self.lookup(ServerEdition::Java, address).await;
+        let status = match result {
+            Ok(status) => status,
+            Err(error) => return error_json(&error),
+        };
+
+        let favicon = match &status.data {
+            ServerData::Java(java) => java.favicon.as_deref(),
+            ServerData::Bedrock(_) => None,
+        };
+        let png = favicon
+            .map(|favicon| {
+                favicon
+                    .strip_prefix("data:image/png;base64,")
+                    .unwrap_or(favicon)
+            })
+            .and_then(|data| general_purpose::STANDARD.decode(data).ok());
+        match png {
+            Some(png) => self.cache_headers(Response::new(200, "image/png", png), age),
+            None => error_response(404, "server has no icon"),
+        }
+    }
+
+    async fn batch(&self, request: &Request, peer: IpAddr) -> Response {
+        let items: Vec<BatchItem> = match serde_json::from_slice(&request.body) {
+            Ok(items) => items,
+            Err(error) => return error_response(400, &format!("invalid batch: {error}")),
+        };
+        if items.len() > MAX_BATCH {
+            return error_response(400, &format!("at most {MAX_BATCH} servers per batch"));
+        }
+        if let Err(response) = self.admit(peer, items.len().max(1)) {
+            return response;
+        }
+
+        let mut servers = Vec::with_capacity(items.len());
+        for item in items {
+            match item.edition.parse::<ServerEdition>() {
+                Ok(edition) => servers.push((edition, item.address)),
+                Err(error) => return error_response(400, &error.to_string()),
+            }
+        }
+
+        let lookups = servers
+            .iter()
+            .map(|(edition, address)| self.lookup(*edition, address));
+        let results = futures::future::join_all(lookups).await;
+
+        let entries: Vec<BatchEntry> = servers
+            .into_iter()
+            .zip(results)
+            .map(|((edition, address), (result, _))| {
+                let (status, error) = match result {
+                    Ok(status) => (Some(status), None),
+                    Err(error) => (None, Some(error)),
+                };
+                BatchEntry {
+                    address,
+                    edition,
+                    status,
+                    error,
+                }
+            })
+            .collect();
+        json_response(200, &entries)
+    }
+
+    /// The cached or fresh result for a server, with the age of the result.
+    async fn lookup(
+        &self,
+        edition: ServerEdition,
+        address: &str,
+    ) -> (Result<ServerStatus, ErrorRecord>, Duration) {
+        let key = (edition, address.to_string());
+        if let Some(entry) = self.cache.lock().unwrap().get(&key)
+            && entry.fetched_at.elapsed() < self.api.cache_ttl
+        {
+            return (entry.result.clone(), entry.fetched_at.elapsed());
+        }
+
+        let result = self
+            .api
+            .client
+            .ping(address, edition)
+            .await
+            .map_err(|error| error.to_record());
+
+        let mut cache = self.cache.lock().unwrap();
+        if cache.len() >= SWEEP_THRESHOLD {
+            let ttl = self.api.cache_ttl;
+            cache.retain(|_, entry| entry.fetched_at.elapsed() < ttl);
+        }
+        cache.insert(
+            key,
+            CacheEntry {
+                fetched_at: Instant::now(),
+                result: result.clone(),
+            },
+        );
+        (result, Duration::ZERO)
+    }
+
+    /// Takes `cost` tokens from `peer`'s bucket, or returns the `429` to send instead. The
+    /// cost is capped at the burst size so a large batch waits for a full bucket instead
+    /// of never fitting.
+    fn admit(&self, peer: IpAddr, cost: usize) -> Result<(), Response> {
+        let (rate, burst) = (self.api.rate, f64::from(self.api.burst));
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= SWEEP_THRESHOLD {
+            // A bucket that would be full again carries no state worth keeping.
+            buckets.retain(|_, bucket| {
+                bucket.tokens + bucket.updated_at.elapsed().as_secs_f64() * rate < burst
+            });
+        }
+
+        let bucket = buckets.entry(peer).or_insert(Bucket {
+            tokens: burst,
+            updated_at: Instant::now(),
+        });
+        bucket.tokens =
+            (bucket.tokens + bucket.updated_at.elapsed().as_secs_f64() * rate).min(burst);
+        bucket.updated_at = Instant::now();
+
+        let cost = (cost as f64).min(burst.max(1.0));
+        if bucket.tokens >= cost {
+            bucket.tokens -= cost;
+            return Ok(());
+        }
+        let wait = if rate > 0.0 {
+            ((cost - bucket.tokens) / rate).ceil()
+        } else {
+            60.0
+        };
+        Err(error_response(429, "rate limit exceeded").with_header("Retry-After", wait.to_string()))
+    }
+
+    fn cache_headers(&self, response: Response, age: Duration) -> Response {
+        let max_age = self.api.cache_ttl.saturating_sub(age).as_secs();
+        response
+            .with_header("Cache-Control", format!("public, max-age={max_age}"))
+            .with_header("Age", age.as_secs().to_string())
+    }
+
+    fn cors(&self, request: &Request, response: Response) -> Response {
+        let origins = &self.api.cors_origins;
+        if origins.is_empty() {
+            return response.with_header("Access-Control-Allow-Origin", "*");
+        }
+        match request.header("origin") {
+            Some(origin) if origins.iter().any(|allowed| allowed == origin) => response
+                .with_header("Access-Control-Allow-Origin", origin)
+                .with_header("Vary", "Origin"),
+            _ => response.with_header("Vary", "Origin"),
+        }
+    }
+}
+
+fn json_response(status: u16, value: &impl Serialize) -> Response {
+    match serde_json::to_vec(value) {
+        Ok(body) => Response::new(status, JSON, body),
+        Err(error) => error_response(500, &error.to_string()),
+    }
+}
+
+fn error_response(status: u16, message: &str) -> Response {
+    json_response(status, &serde_json::json!({ "error": message }))
+}
+
+/// `400` for addresses the client got wrong, `502` for servers that did not answer.
+fn error_json(error: &ErrorRecord) -> Response {
+    let status = match error.kind {
+        ErrorKind::InvalidAddress | ErrorKind::InvalidPort | ErrorKind::InvalidEdition => 400,
+        _ => 502,
+    };
+    json_response(status, &serde_json::json!({ "error": error }))
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::net::{ToSocketAddrs, UdpSocket};
+use tokio::time::sleep;
+
+use super::ServerHandle;
+use crate::models::BedrockStatus;
+use crate::protocol::bedrock;
+use crate::transport::Fault;
+
+/// A UDP responder that answers RakNet unconnected pings with a pong built from a
+/// [`BedrockStatus`].
+///
+/// Replies can be delayed or sabotaged with a [`Fault`]. `Truncate` and `Corrupt` alter the
+/// pong; every other fault drops it, since UDP has no way to refuse or close.
+pub struct BedrockStatusServer {
+    socket: UdpSocket,
+    status: Arc<RwLock<BedrockStatus>>,
+    delay: Duration,
+    fault: Option<Fault>,
+    queued_faults: VecDeque<Fault>,
+}
+
+impl BedrockStatusServer {
+    pub async fn bind(addr: impl ToSocketAddrs, status: BedrockStatus) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(addr).await?,
+            status: Arc::new(RwLock::new(status)),
+            delay: Duration::ZERO,
+            fault: None,
+            queued_faults: VecDeque::new(),
+        })
+    }
+
+    /// Wait this long before sending each pong.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Apply `fault` to every reply.
+    pub fn with_fault(mut self, fault: Fault) -> Self {
+        self.fault = Some(fault);
+        self
+    }
+
+    /// Apply `faults` to the next replies, one per ping, before falling back to normal
+    /// behaviour.
+    pub fn with_faults_once(mut self, faults: impl IntoIterator<Item = Fault>) -> Self {
+        self.queued_faults.extend(faults);
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Answers pings until the socket fails.
+    pub async fn run(self) -> io::Result<()> {
+        let socket = Arc::new(self.socket);
+        let mut faults = self.queued_faults;
+        let mut buf = [0u8; 1500];
+
+        loop {
+            let (len, peer) = socket.recv_from(&mut buf).await?;
+            let Ok(ping) = bedrock::UnconnectedPing::decode(&buf[..len]) else {
+                continue;
+            };
+
+            let fault = faults.pop_front().or_else(|| self.fault.clone());
+            let Some(reply) = pong(&self.status.read().unwrap(), ping.time, fault) else {
+                continue;
+            };
+
+            let socket = socket.clone();
+            let delay = self.delay;
+            tokio::spawn(async move {
+                sleep(delay).await;
+                let _ = socket.send_to(&reply, peer).await;
+            });
+        }
+    }
+
+    /// Runs the responder on a background task.
+    pub fn spawn(self) -> io::Result<ServerHandle<BedrockStatus>> {
+        let addr = self.local_addr()?;
+        let status = self.status.clone();
+        Ok(ServerHandle {
+            addr,
+            status,
+            task: tokio::spawn(self.run()),
+        })
+    }
+}
+
+/// Builds the reply to a ping sent at `time`, or `None` if it should be dropped.
+fn pong(status: &BedrockStatus, time: u64, fault: Option<Fault>) -> Option<Vec<u8>> {
+    let packet = bedrock::UnconnectedPong {
+        time,
+        server_guid: status.server_uid.parse().unwrap_or(0),
+        server_id: bedrock::server_id(status),
+    }
+    .encode();
+
+    match fault {
+        None => Some(packet),
+        Some(Fault::Truncate(n)) => Some(packet[..n.min(packet.len())].to_vec()),
+        Some(Fault::Corrupt(bytes)) => Some(bytes),
+        Some(Fault::Refuse | Fault::Unreachable | Fault::Hang | Fault::Close) => None,
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use super::http::{Request, Response, read_request, write_response};
+use crate::client::McClient;
+use crate::models::{PingResult, ServerData, ServerEdition, ServerInfo};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The latest ping result per server, rendered in the Prometheus text format.
+///
+/// Clones share the same results, so one copy can be handed to a
+/// [`Monitor`](crate::Monitor) and another to a [`MetricsExporter`].
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    results: Arc<RwLock<BTreeMap<(String, &'static str), PingResult>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the stored result for the result's server.
+    pub fn record(&self, result: &PingResult) {
+        let key = (
+            result.target.address.clone(),
+            edition_label(result.target.edition),
+        );
+        self.results.write().unwrap().insert(key, result.clone());
+    }
+
+    /// All stored results as Prometheus gauges.
+    pub fn render(&self) -> String {
+        let results = self.results.read().unwrap();
+        render(results.values())
+    }
+}
+
+/// Serves ping results to Prometheus over HTTP.
+///
+/// `GET /metrics` pings the configured targets, then returns the latest result for every
+/// server, including any recorded elsewhere into the shared [`Metrics`].
+/// `GET /metrics?target=host:port&edition=bedrock` pings just that server and returns
+/// only its metrics, like blackbox_exporter's probe endpoint; the edition defaults to Java.
+pub struct MetricsExporter {
+    listener: TcpListener,
+    client: McClient,
+    targets: Vec<ServerInfo>,
+    metrics: Metrics,
+}
+
+impl MetricsExporter {
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            client: McClient::new(),
+            targets: Vec::new(),
+            metrics: Metrics::new(),
+        })
+    }
+
+    pub fn with_client(mut self, client: McClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Ping `server` on every scrape of `/metrics`.
+    pub fn with_target(mut self, server: ServerInfo) -> Self {
+        self.targets.push(server);
+        self
+    }
+
+    /// Also serve the results recorded into `metrics`, e.g. by a monitor.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves `/metrics` until accepting a connection fails. A scrape without a `target`
+    /// parameter pings every configured target first.
+    pub async fn run(self) -> io::Result<()> {
+        let exporter = Arc::new(self);
+        loop {
+            let (stream, _) = exporter.listener.accept().await?;
+            let exporter = exporter.clone();
+            tokio::spawn(async move {
+                let _ = exporter.serve_connection(stream).await;
+            });
+        }
+    }
+
+    async fn serve_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        let Some(request) = read_request(&mut stream).await? else {
+            return Ok(());
+        };
+        let response = self.respond(&request).await;
+        write_response(&mut stream, response).await
+    }
+
+    async fn respond(&self, request: &Request) -> Response {
+        if request.path != "/metrics" {
+            return Response::text(404, "not found\n");
+        }
+        if request.method != "GET" {
+            return Response::text(405, "only GET is supported\n");
+        }
+
+        let Some(target) = request.query("target") else {
+            for result in self.client.ping_many(&self.targets).await {
+                self.metrics.record(&result);
+            }
+            return Response::new(200, CONTENT_TYPE, self.metrics.render());
+        };
+
+        let edition = match request.query("edition").unwrap_or("java").parse() {
+            Ok(edition) => edition,
+            Err(error) => return Response::text(400, format!("{error}\n")),
+        };
+        let result = self.client.probe(&ServerInfo::new(target, edition)).await;
+        Response::new(200, CONTENT_TYPE, render([&result]))
+    }
+}
+
+fn render<'a>(results: impl IntoIterator<Item = &'a PingResult>) -> String {
+    let mut up = String::new();
+    let mut latency = String::new();
+    let mut players_online = String::new();
+    let mut players_max = String::new();
+    let mut protocol = String::new();
+    let mut duration = String::new();
+
+    for result in results {
+        let labels = format!(
+            "address=\"{}\",edition=\"{}\"",
+            escape(&result.target.address),
+            edition_label(result.target.edition)
+        );
+        let _ = writeln!(up, "mc_up{{{labels}}} {}", u8::from(result.is_online()));
+        let _ = writeln!(
+            duration,
+            "mc_probe_duration_seconds{{{labels}}} {}",
+            result.elapsed / 1000.0
+        );
+        if let Some(ms) = result.latency {
+            let _ = writeln!(latency, "mc_latency_seconds{{{labels}}} {}", ms / 1000.0);
+        }
+
+        let (online, max, protocol_version) = match result.data() {
+            Some(ServerData::Java(java)) => (
+                Some(java.players.online),
+                Some(java.players.max),
+                Some(java.version.protocol),
+            ),
+            Some(ServerData::Bedrock(bedrock)) => (
+                bedrock.online_players.parse().ok(),
+                bedrock.max_players.parse().ok(),
+                bedrock.protocol_version.parse().ok(),
+            ),
+            None => (None, None, None),
+        };
+        if let Some(online) = online {
+            let _ = writeln!(players_online, "mc_players_online{{{labels}}} {online}");
+        }
+        if let Some(max) = max {
+            let _ = writeln!(players_max, "mc_players_max{{{labels}}} {max}");
+        }
+        if let Some(version) = protocol_version {
+            let _ = writeln!(protocol, "mc_protocol_version{{{labels}}} {version}");
+        }
+    }
+
+    let mut out = String::new();
+    for (name, help, samples) in [
+        ("mc_up", "Whether the server answered the last ping.", up),
+        (
+            "mc_latency_seconds",
+            "Round-trip latency of the last ping.",
+            latency,
+        ),
+        ("mc_players_online", "Players online.", players_online),
+        ("mc_players_max", "Player slots.", players_max),
+        (
+            "mc_protocol_version",
+            "Protocol version reported by the server.",
+            protocol,
+        ),
+        (
+            "mc_probe_duration_seconds",
+            "Time taken by the last ping, including DNS and retries.",
+            duration,
+        ),
+    ] {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge");
+        out.push_str(&samples);
+    }
+    out
+}
+
+fn edition_label(edition: ServerEdition) -> &'static str {
+    match edition {
+        ServerEdition::Java => "java",
+        ServerEdition::Bedrock => "bedrock",
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
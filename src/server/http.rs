@@ -0,0 +1,196 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! The little HTTP/1.1 the exporter and API need: one request per connection, small bodies.
+
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Longest request head accepted.
+const MAX_HEAD_LEN: usize = 8192;
+/// Largest request body accepted.
+const MAX_BODY_LEN: usize = 64 * 1024;
+/// Clients that take longer than this to send their request are dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub(crate) struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Self::new(status, "text/plain; charset=utf-8", body.into())
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+/// Reads one request, returning `None` if the client sent something unusable.
+pub(crate) async fn read_request(stream: &mut TcpStream) -> io::Result<Option<Request>> {
+    let mut data = Vec::new();
+    let head_len = loop {
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if data.len() > MAX_HEAD_LEN || !read_more(stream, &mut data).await? {
+            return Ok(None);
+        }
+    };
+
+    let Ok(head) = std::str::from_utf8(&data[..head_len]) else {
+        return Ok(None);
+    };
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Ok(None);
+    };
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key, true), percent_decode(value, true))
+        })
+        .collect();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let mut request = Request {
+        method: method.to_string(),
+        path: percent_decode(path, false),
+        query,
+        headers,
+        body: Vec::new(),
+    };
+
+    let body_len = match request.header("content-length").map(str::parse::<usize>) {
+        None => 0,
+        Some(Ok(len)) if len <= MAX_BODY_LEN => len,
+        Some(_) => return Ok(None),
+    };
+    while data.len() < head_len + body_len {
+        if !read_more(stream, &mut data).await? {
+            return Ok(None);
+        }
+    }
+    request.body = data[head_len..head_len + body_len].to_vec();
+    Ok(Some(request))
+}
+
+/// Appends what the client sends next, returning `false` on EOF or timeout.
+async fn read_more(stream: &mut TcpStream, data: &mut Vec<u8>) -> io::Result<bool> {
+    let mut buf = [0u8; 4096];
+    match timeout(READ_TIMEOUT, stream.read(&mut buf)).await {
+        Ok(read) => {
+            let n = read?;
+            data.extend_from_slice(&buf[..n]);
+            Ok(n > 0)
+        }
+        Err(_) => Ok(false),
+    }
+}
+
+pub(crate) async fn write_response(stream: &mut TcpStream, response: Response) -> io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.shutdown().await
+}
+
+/// Decodes `%XX` escapes, and `+` as a space when `plus_as_space` is set, as it is only
+/// in query components.
+fn percent_decode(input: &str, plus_as_space: bool) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' if plus_as_space => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        out.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        _ => "",
+    }
+}
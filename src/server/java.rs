@@ -0,0 +1,122 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::time::timeout;
+
+use super::ServerHandle;
+use crate::error::McError;
+use crate::models::JavaStatus;
+use crate::protocol::{FrameDecoder, RawPacket, java};
+
+/// Longest request packet accepted; a handshake with the longest allowed address fits.
+const MAX_REQUEST_LEN: usize = 2048;
+/// Connections that send nothing for this long are closed.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A TCP server that answers Java Edition status and ping requests with a [`JavaStatus`].
+///
+/// Login attempts are not supported; such connections are closed after the handshake.
+pub struct JavaStatusServer {
+    listener: TcpListener,
+    status: Arc<RwLock<JavaStatus>>,
+}
+
+impl JavaStatusServer {
+    pub async fn bind(addr: impl ToSocketAddrs, status: JavaStatus) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            status: Arc::new(RwLock::new(status)),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answers status pings with the current status, handling each connection on its own
+    /// task. Returns only if accepting a connection fails.
+    pub async fn run(self) -> io::Result<()> {
+        loop {
+            let (stream, _) = self.listener.accept().await?;
+            let status = self.status.clone();
+            tokio::spawn(async move {
+                let status = || async { status.read().unwrap().clone() };
+                let _ = serve_connection(stream, status).await;
+            });
+        }
+    }
+
+    /// Runs [`run`](Self::run) on a background task. The handle's status is the one sent
+    /// to later pings.
+    pub fn spawn(self) -> io::Result<ServerHandle<JavaStatus>> {
+        let addr = self.local_addr()?;
+        let status = self.status.clone();
+        Ok(ServerHandle {
+            addr,
+            status,
+            task: tokio::spawn(self.run()),
+        })
+    }
+}
+
+/// Handles one client connection, asking `status` for the status to send each time one is
+/// requested.
+pub(crate) async fn serve_connection<F, Fut>(
+    mut stream: TcpStream,
+    status: F,
+) -> Result<(), McError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = JavaStatus>,
+{
+    stream.set_nodelay(true)?;
+
+    let mut decoder = FrameDecoder::new(MAX_REQUEST_LEN);
+    let handshake = java::Handshake::decode(&next_packet(&mut stream, &mut decoder).await?)?;
+    if handshake.next_state != java::NextState::Status {
+        return Ok(());
+    }
+
+    loop {
+        let packet = next_packet(&mut stream, &mut decoder).await?;
+
+        if java::decode_status_request(&packet).is_ok() {
+            let json = java::status_json(&status().await).to_string();
+            stream
+                .write_all(&java::encode_status_response(&json))
+                .await?;
+        } else {
+            // The client is done once it has its pong; vanilla closes here too.
+            let payload = java::decode_ping(&packet)?;
+            stream.write_all(&java::encode_pong(payload)).await?;
+            return Ok(());
+        }
+    }
+}
+
+async fn next_packet(
+    stream: &mut TcpStream,
+    decoder: &mut FrameDecoder,
+) -> Result<RawPacket, McError> {
+    let mut buf = [0u8; 512];
+    loop {
+        if let Some(packet) = decoder.next_packet()? {
+            return Ok(packet);
+        }
+
+        let n = timeout(IDLE_TIMEOUT, stream.read(&mut buf))
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+        if n == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        decoder.push(&buf[..n]);
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Servers that answer status pings, built on the same models and codecs as the client.
+//!
+//! Useful as test fixtures: spawn one on `127.0.0.1:0`, point an [`McClient`](crate::McClient)
+//! at [`ServerHandle::local_addr`] and change the advertised status while it runs.
+
+#[cfg(feature = "http-api")]
+mod api;
+mod bedrock;
+#[cfg(feature = "exporter")]
+mod exporter;
+#[cfg(any(feature = "exporter", feature = "http-api"))]
+// Each feature uses a different part of it.
+#[cfg_attr(not(all(feature = "exporter", feature = "http-api")), allow(dead_code))]
+mod http;
+mod java;
+mod proxy;
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "http-api")]
+pub use api::StatusApi;
+pub use bedrock::BedrockStatusServer;
+#[cfg(feature = "exporter")]
+pub use exporter::{Metrics, MetricsExporter};
+pub use java::JavaStatusServer;
+pub use proxy::StatusProxy;
+
+/// A server running in the background. Dropping the handle stops the server.
+#[derive(Debug)]
+pub struct ServerHandle<S> {
+    addr: SocketAddr,
+    status: Arc<RwLock<S>>,
+    task: JoinHandle<io::Result<()>>,
+}
+
+impl<S: Clone> ServerHandle<S> {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The status currently being served.
+    pub fn status(&self) -> S {
+        self.status.read().unwrap().clone()
+    }
+
+    /// Replaces the status served to subsequent requests.
+    pub fn set_status(&self, status: S) {
+        *self.status.write().unwrap() = status;
+    }
+
+    /// Stops the server and waits for it to exit.
+    pub async fn shutdown(mut self) {
+        self.task.abort();
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl<S> Drop for ServerHandle<S> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
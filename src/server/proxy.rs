@@ -0,0 +1,164 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use super::ServerHandle;
+use super::java::serve_connection;
+use crate::client::McClient;
+use crate::models::{JavaStatus, ServerData};
+
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A Java status server that mirrors an upstream server's status.
+///
+/// The upstream is pinged with an [`McClient`] at most once per refresh interval. While it
+/// is offline, the proxy serves the offline status if one is set and otherwise the last
+/// status the upstream returned. In maintenance mode the maintenance status is served
+/// without contacting the upstream at all.
+pub struct StatusProxy {
+    listener: TcpListener,
+    upstream: String,
+    client: McClient,
+    refresh_interval: Duration,
+    motd: Option<String>,
+    offline_status: Option<JavaStatus>,
+    maintenance: Arc<RwLock<Option<JavaStatus>>>,
+}
+
+struct ProxyState {
+    proxy: StatusProxy,
+    cache: Mutex<UpstreamCache>,
+}
+
+#[derive(Default)]
+struct UpstreamCache {
+    checked_at: Option<Instant>,
+    online: bool,
+    last_status: Option<JavaStatus>,
+}
+
+impl StatusProxy {
+    pub async fn bind(addr: impl ToSocketAddrs, upstream: &str) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            upstream: upstream.to_string(),
+            client: McClient::new(),
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            motd: None,
+            offline_status: None,
+            maintenance: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    /// The client used to ping the upstream.
+    pub fn with_client(mut self, client: McClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// How long an upstream status is reused before pinging again.
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Replace the upstream's MOTD in every status served from it.
+    pub fn with_motd(mut self, motd: impl Into<String>) -> Self {
+        self.motd = Some(motd.into());
+        self
+    }
+
+    /// Serve this status while the upstream is offline.
+    pub fn with_offline_status(mut self, status: JavaStatus) -> Self {
+        self.offline_status = Some(status);
+        self
+    }
+
+    /// Start in maintenance mode, serving `status`.
+    pub fn with_maintenance(self, status: JavaStatus) -> Self {
+        *self.maintenance.write().unwrap() = Some(status);
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answers pings with the maintenance status, the upstream status or its fallbacks,
+    /// in that order. The upstream cache lives as long as this call.
+    pub async fn run(self) -> io::Result<()> {
+        let state = Arc::new(ProxyState {
+            proxy: self,
+            cache: Mutex::new(UpstreamCache::default()),
+        });
+
+        loop {
+            let (stream, _) = state.proxy.listener.accept().await?;
+            let state = state.clone();
+            tokio::spawn(async move {
+                let _ = serve_connection(stream, || state.current_status()).await;
+            });
+        }
+    }
+
+    /// Runs the proxy on a background task. The handle's status is the maintenance
+    /// status: set it to `Some` to enter maintenance mode and `None` to leave it.
+    pub fn spawn(self) -> io::Result<ServerHandle<Option<JavaStatus>>> {
+        let addr = self.local_addr()?;
+        let status = self.maintenance.clone();
+        Ok(ServerHandle {
+            addr,
+            status,
+            task: tokio::spawn(self.run()),
+        })
+    }
+}
+
+impl ProxyState {
+    async fn current_status(&self) -> JavaStatus {
+        let proxy = &self.proxy;
+        if let Some(status) = proxy.maintenance.read().unwrap().clone() {
+            return status;
+        }
+
+        let mut cache = self.cache.lock().await;
+        if cache
+            .checked_at
+            .is_none_or(|at| at.elapsed() >= proxy.refresh_interval)
+        {
+            cache.online = match proxy.client.ping_java(&proxy.upstream).await {
+                Ok(status) => match status.data {
+                    ServerData::Java(status) => {
+                        cache.last_status = Some(status);
+                        true
+                    }
+                    ServerData::Bedrock(_) => false,
+                },
+                Err(_) => false,
+            };
+            cache.checked_at = Some(Instant::now());
+        }
+
+        if !cache.online
+            && let Some(status) = &proxy.offline_status
+        {
+            return status.clone();
+        }
+
+        let mut status = cache
+            .last_status
+            .clone()
+            .unwrap_or_else(|| JavaStatus::new("Offline", -1, "Server is offline"));
+        if let Some(motd) = &proxy.motd {
+            status.description = motd.clone();
+        }
+        status
+    }
+}
@@ -0,0 +1,592 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! The Java Edition client's saved server list, `servers.dat`.
+//!
+//! The file is uncompressed NBT: a root compound holding a `servers` list of compounds
+//! with `name`, `ip`, an optional base64 PNG `icon` and a `hidden` flag. Tags this module
+//! does not know are kept, so a file read and written back loses nothing.
+
+use std::fs;
+use std::path::Path;
+
+use base64::{Engine as _, engine::general_purpose};
+
+use crate::error::McError;
+use crate::files::write_atomic;
+use crate::models::{JavaStatus, ServerEdition, ServerInfo};
+
+/// Deepest nesting of lists and compounds accepted.
+const MAX_DEPTH: usize = 512;
+
+/// One entry of the multiplayer screen.
+#[derive(Debug, Clone)]
+pub struct SavedServer {
+    pub name: String,
+    /// Always a Java server; the address is the `ip` tag as the player typed it.
+    pub server: ServerInfo,
+    /// The server's icon as base64 PNG, without a `data:` prefix.
+    pub icon: Option<String>,
+    /// Hidden entries are kept by the client but not listed.
+    pub hidden: bool,
+    /// The player's answer to the server resource pack prompt, if they gave one.
+    pub accept_textures: Option<bool>,
+    extra: Vec<(String, Tag)>,
+}
+
+/// A whole `servers.dat`.
+#[derive(Debug, Clone, Default)]
+pub struct ServersDat {
+    pub servers: Vec<SavedServer>,
+    extra: Vec<(String, Tag)>,
+}
+
+impl SavedServer {
+    pub fn new(name: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            server: ServerInfo::new(address, ServerEdition::Java),
+            icon: None,
+            hidden: false,
+            accept_textures: None,
+            extra: Vec::new(),
+        }
+    }
+
+    /// The decoded icon, if there is one and it is valid base64.
+    pub fn icon_png(&self) -> Option<Vec<u8>> {
+        general_purpose::STANDARD.decode(self.icon.as_deref()?).ok()
+    }
+
+    pub fn set_icon_png(&mut self, png: &[u8]) {
+        self.icon = Some(general_purpose::STANDARD.encode(png));
+    }
+
+    /// Takes the server's current icon from a fresh status, as the client does when it
+    /// refreshes the list.
+    pub fn update_from(&mut self, status: &JavaStatus) {
+        self.icon = status.favicon.as_deref().map(|favicon| {
+            favicon
+                .strip_prefix("data:image/png;base64,")
+                .unwrap_or(favicon)
+                .to_string()
+        });
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self, McError> {
+        let Tag::Compound(fields) = tag else {
+            return Err(McError::Nbt("server entry is not a compound".into()));
+        };
+
+        let mut server = SavedServer::new("", "");
+        let mut address = None;
+        for (key, value) in fields {
+            match (key.as_str(), value) {
+                ("name", Tag::String(name)) => server.name = name,
+                ("ip", Tag::String(ip)) => address = Some(ip),
+                ("icon", Tag::String(icon)) => server.icon = Some(icon),
+                ("hidden", Tag::Byte(hidden)) => server.hidden = hidden != 0,
+                ("acceptTextures", Tag::Byte(accept)) => server.accept_textures = Some(accept != 0),
+                (_, value) => server.extra.push((key, value)),
+            }
+        }
+        server.server.address =
+            address.ok_or_else(|| McError::Nbt("server entry has no ip".into()))?;
+        Ok(server)
+    }
+
+    fn to_tag(&self) -> Tag {
+        let mut fields = vec![
+            ("name".to_string(), Tag::String(self.name.clone())),
+            ("ip".to_string(), Tag::String(self.server.address.clone())),
+        ];
+        if let Some(icon) = &self.icon {
+            fields.push(("icon".to_string(), Tag::String(icon.clone())));
+        }
+        fields.push(("hidden".to_string(), Tag::Byte(self.hidden.into())));
+        if let Some(accept) = self.accept_textures {
+            fields.push(("acceptTextures".to_string(), Tag::Byte(accept.into())));
+        }
+        fields.extend(self.extra.iter().cloned());
+        Tag::Compound(fields)
+    }
+}
+
+impl From<ServerInfo> for SavedServer {
+    /// An entry named after its address.
+    fn from(server: ServerInfo) -> Self {
+        let mut saved = SavedServer::new(server.address.clone(), "");
+        saved.server = server;
+        saved
+    }
+}
+
+impl ServersDat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, McError> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            return Err(McError::Nbt("gzip-compressed NBT is not supported".into()));
+        }
+
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.u8()? != TAG_COMPOUND {
+            return Err(McError::Nbt("root tag is not a compound".into()));
+        }
+        reader.string()?;
+        let Tag::Compound(fields) = reader.payload(TAG_COMPOUND, 0)? else {
+            unreachable!("a compound payload reads as a compound");
+        };
+
+        let mut dat = ServersDat::new();
+        for (key, value) in fields {
+            match (key.as_str(), value) {
+                ("servers", Tag::List(_, entries)) => {
+                    dat.servers = entries
+                        .into_iter()
+                        .map(SavedServer::from_tag)
+                        .collect::<Result<_, _>>()?;
+                }
+                (_, value) => dat.extra.push((key, value)),
+            }
+        }
+        Ok(dat)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, McError> {
+        let mut fields = vec![(
+            "servers".to_string(),
+            Tag::List(
+                TAG_COMPOUND,
+                self.servers.iter().map(SavedServer::to_tag).collect(),
+            ),
+        )];
+        fields.extend(self.extra.iter().cloned());
+
+        let mut out = vec![TAG_COMPOUND];
+        write_string(&mut out, "")?;
+        Tag::Compound(fields).write(&mut out)?;
+        Ok(out)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, McError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Replaces the file in one step, so the game never reads a half-written list.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), McError> {
+        write_atomic(path.as_ref(), &self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Every saved server, hidden ones included, ready for `ping_many`.
+    pub fn server_infos(&self) -> Vec<ServerInfo> {
+        self.servers
+            .iter()
+            .map(|saved| saved.server.clone())
+            .collect()
+    }
+}
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+#[derive(Debug, Clone)]
+enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// The element type, kept so empty lists round-trip unchanged.
+    List(u8, Vec<Tag>),
+    /// Fields in file order.
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => TAG_BYTE,
+            Tag::Short(_) => TAG_SHORT,
+            Tag::Int(_) => TAG_INT,
+            Tag::Long(_) => TAG_LONG,
+            Tag::Float(_) => TAG_FLOAT,
+            Tag::Double(_) => TAG_DOUBLE,
+            Tag::ByteArray(_) => TAG_BYTE_ARRAY,
+            Tag::String(_) => TAG_STRING,
+            Tag::List(..) => TAG_LIST,
+            Tag::Compound(_) => TAG_COMPOUND,
+            Tag::IntArray(_) => TAG_INT_ARRAY,
+            Tag::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// Writes the payload only; the id and name belong to the enclosing compound.
+    fn write(&self, out: &mut Vec<u8>) -> Result<(), McError> {
+        match self {
+            Tag::Byte(value) => out.push(*value as u8),
+            Tag::Short(value) => out.extend(value.to_be_bytes()),
+            Tag::Int(value) => out.extend(value.to_be_bytes()),
+            Tag::Long(value) => out.extend(value.to_be_bytes()),
+            Tag::Float(value) => out.extend(value.to_be_bytes()),
+            Tag::Double(value) => out.extend(value.to_be_bytes()),
+            Tag::ByteArray(values) => {
+                write_len(out, values.len())?;
+                out.extend(values.iter().map(|value| *value as u8));
+            }
+            Tag::String(value) => write_string(out, value)?,
+            Tag::List(id, values) => {
+                let id = values.first().map_or(*id, Tag::id);
+                if values.iter().any(|value| value.id() != id) {
+                    return Err(McError::Nbt("list elements differ in type".into()));
+                }
+                out.push(id);
+                write_len(out, values.len())?;
+                for value in values {
+                    value.write(out)?;
+                }
+            }
+            Tag::Compound(fields) => {
+                for (name, value) in fields {
+                    out.push(value.id());
+                    write_string(out, name)?;
+                    value.write(out)?;
+                }
+                out.push(TAG_END);
+            }
+            Tag::IntArray(values) => {
+                write_len(out, values.len())?;
+                values
+                    .iter()
+                    .for_each(|value| out.extend(value.to_be_bytes()));
+            }
+            Tag::LongArray(values) => {
+                write_len(out, values.len())?;
+                values
+                    .iter()
+                    .for_each(|value| out.extend(value.to_be_bytes()));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], McError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| McError::Nbt(format!("unexpected end of data at byte {}", self.pos)))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], McError> {
+        Ok(self.take(N)?.try_into().expect("took exactly N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, McError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// A length prefix, checked against what is left so bad data cannot force a huge
+    /// allocation.
+    fn len(&mut self, element_size: usize) -> Result<usize, McError> {
+        let len = i32::from_be_bytes(self.array()?);
+        let len = usize::try_from(len)
+            .map_err(|_| McError::Nbt(format!("negative length at byte {}", self.pos)))?;
+        if len.saturating_mul(element_size) > self.bytes.len() - self.pos {
+            return Err(McError::Nbt(format!(
+                "length {len} at byte {} runs past the end",
+                self.pos
+            )));
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, McError> {
+        let len = u16::from_be_bytes(self.array()?);
+        let pos = self.pos;
+        decode_mutf8(self.take(len.into())?)
+            .ok_or_else(|| McError::Nbt(format!("invalid string at byte {pos}")))
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, McError> {
+        if depth > MAX_DEPTH {
+            return Err(McError::Nbt("tags nested too deeply".into()));
+        }
+        Ok(match id {
+            TAG_BYTE => Tag::Byte(self.u8()? as i8),
+            TAG_SHORT => Tag::Short(i16::from_be_bytes(self.array()?)),
+            TAG_INT => Tag::Int(i32::from_be_bytes(self.array()?)),
+            TAG_LONG => Tag::Long(i64::from_be_bytes(self.array()?)),
+            TAG_FLOAT => Tag::Float(f32::from_be_bytes(self.array()?)),
+            TAG_DOUBLE => Tag::Double(f64::from_be_bytes(self.array()?)),
+            TAG_BYTE_ARRAY => {
+                let len = self.len(1)?;
+                Tag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            TAG_STRING => Tag::String(self.string()?),
+            TAG_LIST => {
+                let element = self.u8()?;
+                let len = self.len(1)?;
+                if element == TAG_END && len > 0 {
+                    return Err(McError::Nbt("non-empty list of end tags".into()));
+                }
+                let values = (0..len)
+                    .map(|_| self.payload(element, depth + 1))
+                    .collect::<Result<_, _>>()?;
+                Tag::List(element, values)
+            }
+            TAG_COMPOUND => {
+                let mut fields = Vec::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == TAG_END {
+                        break;
+                    }
+                    let name = self.string()?;
+                    fields.push((name, self.payload(id, depth + 1)?));
+                }
+                Tag::Compound(fields)
+            }
+            TAG_INT_ARRAY => {
+                let len = self.len(4)?;
+                Tag::IntArray(
+                    (0..len)
+                        .map(|_| Ok(i32::from_be_bytes(self.array()?)))
+                        .collect::<Result<_, McError>>()?,
+                )
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.len(8)?;
+                Tag::LongArray(
+                    (0..len)
+                        .map(|_| Ok(i64::from_be_bytes(self.array()?)))
+                        .collect::<Result<_, McError>>()?,
+                )
+            }
+            id => {
+                return Err(McError::Nbt(format!(
+                    "unknown tag type {id} at byte {}",
+                    self.pos
+                )));
+            }
+        })
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), McError> {
+    let len = i32::try_from(len).map_err(|_| McError::Nbt("array too long".into()))?;
+    out.extend(len.to_be_bytes());
+    Ok(())
+}
+
+fn write_string(out: &mut Vec<u8>, value: &str) -> Result<(), McError> {
+    let bytes = encode_mutf8(value);
+    let len = u16::try_from(bytes.len())
+        .map_err(|_| McError::Nbt(format!("string of {} bytes is too long", bytes.len())))?;
+    out.extend(len.to_be_bytes());
+    out.extend(bytes);
+    Ok(())
+}
+
+/// Java's modified UTF-8: NUL takes two bytes and characters outside the BMP are
+/// written as two three-byte surrogates.
+fn encode_mutf8(value: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7f => out.push(unit as u8),
+            0x00 | 0x80..=0x7ff => {
+                out.push(0xc0 | (unit >> 6) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                out.push(0xe0 | (unit >> 12) as u8);
+                out.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    out
+}
+
+/// Decodes modified UTF-8, also accepting the plain UTF-8 some tools write.
+fn decode_mutf8(bytes: &[u8]) -> Option<String> {
+    let continuation = |i: usize| {
+        bytes
+            .get(i)
+            .filter(|b| *b & 0xc0 == 0x80)
+            .map(|b| u16::from(b & 0x3f))
+    };
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = u16::from(bytes[i]);
+        match bytes[i] {
+            0x00..=0x7f => {
+                units.push(lead);
+                i += 1;
+            }
+            0xc0..=0xdf => {
+                units.push((lead & 0x1f) << 6 | continuation(i + 1)?);
+                i += 2;
+            }
+            0xe0..=0xef => {
+                units.push((lead & 0x0f) << 12 | continuation(i + 1)? << 6 | continuation(i + 2)?);
+                i += 3;
+            }
+            // Four-byte sequences only appear in plain UTF-8.
+            _ => return String::from_utf8(bytes.to_vec()).ok(),
+        }
+    }
+    String::from_utf16(&units).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends a named tag header: type id and name.
+    fn named(out: &mut Vec<u8>, id: u8, name: &str) {
+        out.push(id);
+        write_string(out, name).unwrap();
+    }
+
+    fn string(out: &mut Vec<u8>, name: &str, value: &str) {
+        named(out, TAG_STRING, name);
+        write_string(out, value).unwrap();
+    }
+
+    /// A file as the client writes it, with tags this module does not model at the
+    /// root and in an entry.
+    fn sample_file() -> Vec<u8> {
+        let mut out = Vec::new();
+        named(&mut out, TAG_COMPOUND, "");
+
+        named(&mut out, TAG_LIST, "servers");
+        out.push(TAG_COMPOUND);
+        out.extend(2i32.to_be_bytes());
+
+        string(&mut out, "name", "Lobby \u{0} 🎮");
+        string(&mut out, "ip", "play.example.com");
+        string(&mut out, "icon", "iVBORw0KGgo=");
+        named(&mut out, TAG_BYTE, "hidden");
+        out.push(0);
+        named(&mut out, TAG_BYTE, "acceptTextures");
+        out.push(1);
+        named(&mut out, TAG_LONG_ARRAY, "uuid");
+        out.extend(2i32.to_be_bytes());
+        out.extend(7i64.to_be_bytes());
+        out.extend((-1i64).to_be_bytes());
+        named(&mut out, TAG_COMPOUND, "modded");
+        named(&mut out, TAG_LIST, "empty");
+        out.push(TAG_INT);
+        out.extend(0i32.to_be_bytes());
+        named(&mut out, TAG_DOUBLE, "weight");
+        out.extend(0.5f64.to_be_bytes());
+        out.push(TAG_END);
+        out.push(TAG_END);
+
+        string(&mut out, "name", "Hidden");
+        string(&mut out, "ip", "10.0.0.2:25570");
+        named(&mut out, TAG_BYTE, "hidden");
+        out.push(1);
+        out.push(TAG_END);
+
+        named(&mut out, TAG_INT, "version");
+        out.extend(3i32.to_be_bytes());
+        out.push(TAG_END);
+        out
+    }
+
+    #[test]
+    fn reads_known_fields() {
+        let dat = ServersDat::from_bytes(&sample_file()).unwrap();
+
+        assert_eq!(dat.servers.len(), 2);
+        let lobby = &dat.servers[0];
+        assert_eq!(lobby.name, "Lobby \u{0} 🎮");
+        assert_eq!(lobby.server.address, "play.example.com");
+        assert_eq!(lobby.icon.as_deref(), Some("iVBORw0KGgo="));
+        assert!(!lobby.hidden);
+        assert_eq!(lobby.accept_textures, Some(true));
+        assert_eq!(lobby.extra.len(), 2);
+
+        let hidden = &dat.servers[1];
+        assert!(hidden.hidden);
+        assert_eq!(hidden.accept_textures, None);
+        assert_eq!(dat.extra.len(), 1);
+    }
+
+    #[test]
+    fn round_trip_keeps_unknown_tags() {
+        let bytes = sample_file();
+        let dat = ServersDat::from_bytes(&bytes).unwrap();
+        assert_eq!(dat.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn edits_keep_unknown_tags() {
+        let mut dat = ServersDat::from_bytes(&sample_file()).unwrap();
+        dat.servers[0].name = "Renamed".to_string();
+        dat.servers.push(SavedServer::new("New", "new.example.com"));
+
+        let reread = ServersDat::from_bytes(&dat.to_bytes().unwrap()).unwrap();
+        assert_eq!(reread.servers.len(), 3);
+        assert_eq!(reread.servers[0].name, "Renamed");
+        assert_eq!(reread.servers[0].extra.len(), 2);
+        assert_eq!(reread.servers[2].server.address, "new.example.com");
+        assert_eq!(reread.extra.len(), 1);
+    }
+
+    #[test]
+    fn save_replaces_the_file_without_leftovers() {
+        let dir = std::env::temp_dir().join(format!("mc-servers-dat-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("servers.dat");
+        fs::write(&path, b"old").unwrap();
+
+        let dat = ServersDat::from_bytes(&sample_file()).unwrap();
+        dat.save(&path).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), sample_file());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let bytes = sample_file();
+        assert!(matches!(
+            ServersDat::from_bytes(&bytes[..bytes.len() / 2]),
+            Err(McError::Nbt(_))
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::{Instant, timeout};
+
+use crate::error::{McError, Phase};
+
+/// Time limits for a single ping.
+///
+/// `dns`, `connect` and `read` bound each individual operation of that kind (`read` also
+/// covers sends). `total` is an overall deadline for the whole ping, including DNS,
+/// rate-limit waits and retries; every operation is cut short once it is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timeouts {
+    pub dns: Duration,
+    pub connect: Duration,
+    pub read: Duration,
+    pub total: Option<Duration>,
+}
+
+/// 10 seconds per operation and 30 seconds for the whole ping.
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self::uniform(Duration::from_secs(10)).with_total(Duration::from_secs(30))
+    }
+}
+
+impl Timeouts {
+    /// Use the same limit for every operation and no overall deadline.
+    pub fn uniform(timeout: Duration) -> Self {
+        Self {
+            dns: timeout,
+            connect: timeout,
+            read: timeout,
+            total: None,
+        }
+    }
+
+    pub fn with_dns(mut self, timeout: Duration) -> Self {
+        self.dns = timeout;
+        self
+    }
+
+    pub fn with_connect(mut self, timeout: Duration) -> Self {
+        self.connect = timeout;
+        self
+    }
+
+    pub fn with_read(mut self, timeout: Duration) -> Self {
+        self.read = timeout;
+        self
+    }
+
+    pub fn with_total(mut self, deadline: Duration) -> Self {
+        self.total = Some(deadline);
+        self
+    }
+}
+
+/// The time left for one ping, tracked from when it started.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Budget {
+    timeouts: Timeouts,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub(crate) fn start(timeouts: Timeouts) -> Self {
+        Self {
+            timeouts,
+            deadline: timeouts.total.map(|total| Instant::now() + total),
+        }
+    }
+
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    fn for_phase(&self, phase: Phase) -> Duration {
+        let limit = match phase {
+            Phase::Resolve => self.timeouts.dns,
+            Phase::Connect => self.timeouts.connect,
+            Phase::Handshake | Phase::Read | Phase::Parse => self.timeouts.read,
+        };
+        self.remaining()
+            .map_or(limit, |remaining| limit.min(remaining))
+    }
+
+    /// Runs `future` with the limit for `phase`, reporting a timeout for that phase.
+    pub(crate) async fn limit<F: Future>(
+        &self,
+        phase: Phase,
+        addr: Option<SocketAddr>,
+        future: F,
+    ) -> Result<F::Output, McError> {
+        timeout(self.for_phase(phase), future)
+            .await
+            .map_err(|_| McError::timeout(phase, addr))
+    }
+
+    /// Runs `future` until the overall deadline only, ignoring the limit for `phase`;
+    /// a timeout is reported for that phase.
+    pub(crate) async fn until_deadline<F: Future>(
+        &self,
+        phase: Phase,
+        addr: Option<SocketAddr>,
+        future: F,
+    ) -> Result<F::Output, McError> {
+        match self.remaining() {
+            Some(remaining) => timeout(remaining, future)
+                .await
+                .map_err(|_| McError::timeout(phase, addr)),
+            None => Ok(future.await),
+        }
+    }
+}
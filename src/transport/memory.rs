@@ -0,0 +1,355 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::sync::mpsc;
+use tokio::time::{Instant, sleep, sleep_until};
+
+use super::{Datagram, SrvRecord, Stream, Transport};
+use crate::error::{DnsErrorKind, McError};
+use crate::protocol::{FrameDecoder, bedrock, java};
+
+const DUPLEX_BUFFER: usize = 64 * 1024;
+
+/// A failure to inject for one attempt against a [`MemoryTransport`] endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// The connection is refused (TCP) or answered with a port-unreachable error (UDP).
+    Refuse,
+    /// The host cannot be reached.
+    Unreachable,
+    /// The request is accepted but never answered.
+    Hang,
+    /// The connection is closed right after the request, without a reply.
+    Close,
+    /// Only the first `n` bytes of the reply are sent.
+    Truncate(usize),
+    /// The reply is replaced by these bytes.
+    Corrupt(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+enum Reply {
+    JavaStatus(String),
+    BedrockPong(String),
+    Raw(Vec<u8>),
+}
+
+#[derive(Debug, Default)]
+struct Endpoint {
+    reply: Option<Reply>,
+    latency: Duration,
+    fault: Option<Fault>,
+    queued_faults: VecDeque<Fault>,
+    attempts: usize,
+}
+
+impl Endpoint {
+    /// Records an attempt and returns the fault to apply to it, if any.
+    fn next_attempt(&mut self) -> Option<Fault> {
+        self.attempts += 1;
+        self.queued_faults
+            .pop_front()
+            .or_else(|| self.fault.clone())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    hosts: Mutex<HashMap<String, Result<Vec<IpAddr>, DnsErrorKind>>>,
+    srv: Mutex<HashMap<String, SrvRecord>>,
+    endpoints: Mutex<HashMap<SocketAddr, Endpoint>>,
+}
+
+/// An in-process [`Transport`] that answers pings from a script.
+///
+/// Register hostnames and replies per address, then hand the transport to
+/// [`McClient::with_transport`](crate::McClient::with_transport). Clones share the same
+/// script, so a test can keep one to inspect [`attempts`](Self::attempts) afterwards.
+/// Addresses without a reply refuse connections; unknown hostnames fail with `NXDOMAIN`.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransport {
+    inner: Arc<Inner>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_host(self, host: &str, addrs: impl IntoIterator<Item = IpAddr>) -> Self {
+        self.inner
+            .hosts
+            .lock()
+            .unwrap()
+            .insert(host.to_string(), Ok(addrs.into_iter().collect()));
+        self
+    }
+
+    pub fn with_dns_error(self, host: &str, kind: DnsErrorKind) -> Self {
+        self.inner
+            .hosts
+            .lock()
+            .unwrap()
+            .insert(host.to_string(), Err(kind));
+        self
+    }
+
+    pub fn with_srv(self, host: &str, target: &str, port: u16) -> Self {
+        self.inner
+            .srv
+            .lock()
+            .unwrap()
+            .insert(host.to_string(), (target.to_string(), port));
+        self
+    }
+
+    /// Answer Java status requests to `addr` with this JSON document.
+    pub fn with_java_status(self, addr: SocketAddr, json: impl Into<String>) -> Self {
+        self.update(addr, |e| e.reply = Some(Reply::JavaStatus(json.into())))
+    }
+
+    /// Answer Bedrock pings to `addr` with a pong carrying this server ID string.
+    pub fn with_bedrock_pong(self, addr: SocketAddr, server_id: impl Into<String>) -> Self {
+        self.update(addr, |e| {
+            e.reply = Some(Reply::BedrockPong(server_id.into()))
+        })
+    }
+
+    /// Answer any request to `addr` with these bytes verbatim.
+    pub fn with_raw_reply(self, addr: SocketAddr, bytes: impl Into<Vec<u8>>) -> Self {
+        self.update(addr, |e| e.reply = Some(Reply::Raw(bytes.into())))
+    }
+
+    /// Delay every reply from `addr` by `latency`.
+    pub fn with_latency(self, addr: SocketAddr, latency: Duration) -> Self {
+        self.update(addr, |e| e.latency = latency)
+    }
+
+    /// Apply `fault` to every attempt against `addr`.
+    pub fn with_fault(self, addr: SocketAddr, fault: Fault) -> Self {
+        self.update(addr, |e| e.fault = Some(fault))
+    }
+
+    /// Apply `faults` to the next attempts against `addr`, one per attempt, before
+    /// falling back to normal behaviour.
+    pub fn with_faults_once(
+        self,
+        addr: SocketAddr,
+        faults: impl IntoIterator<Item = Fault>,
+    ) -> Self {
+        self.update(addr, |e| e.queued_faults.extend(faults))
+    }
+
+    /// Number of connections or datagrams `addr` has received.
+    pub fn attempts(&self, addr: SocketAddr) -> usize {
+        self.inner
+            .endpoints
+            .lock()
+            .unwrap()
+            .get(&addr)
+            .map_or(0, |e| e.attempts)
+    }
+
+    fn update(self, addr: SocketAddr, f: impl FnOnce(&mut Endpoint)) -> Self {
+        f(self
+            .inner
+            .endpoints
+            .lock()
+            .unwrap()
+            .entry(addr)
+            .or_default());
+        self
+    }
+
+    /// Records an attempt against `addr`, returning its reply, latency and fault.
+    fn attempt(&self, addr: SocketAddr) -> (Option<Reply>, Duration, Option<Fault>) {
+        let mut endpoints = self.inner.endpoints.lock().unwrap();
+        let endpoint = endpoints.entry(addr).or_default();
+        let fault = endpoint.next_attempt();
+        (endpoint.reply.clone(), endpoint.latency, fault)
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn connect_tcp(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Stream>>> {
+        Box::pin(async move {
+            let (reply, latency, fault) = self.attempt(addr);
+            match (&reply, &fault) {
+                (_, Some(Fault::Refuse)) | (None, None) => {
+                    return Err(io::ErrorKind::ConnectionRefused.into());
+                }
+                (_, Some(Fault::Unreachable)) => {
+                    return Err(io::ErrorKind::HostUnreachable.into());
+                }
+                _ => {}
+            }
+
+            let (client, server) = tokio::io::duplex(DUPLEX_BUFFER);
+            tokio::spawn(serve_tcp(server, reply, latency, fault));
+            Ok(Box::new(client) as Box<dyn Stream>)
+        })
+    }
+
+    fn bind_udp(&self, _local: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>> {
+        Box::pin(async move {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            Ok(Box::new(MemoryDatagram {
+                transport: self.clone(),
+                sender,
+                receiver: tokio::sync::Mutex::new(receiver),
+            }) as Box<dyn Datagram>)
+        })
+    }
+
+    fn resolve<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Vec<IpAddr>, McError>> {
+        Box::pin(async move {
+            let result = self.inner.hosts.lock().unwrap().get(host).cloned();
+            match result {
+                Some(Ok(addrs)) => Ok(addrs),
+                Some(Err(kind)) => Err(McError::DnsError {
+                    host: host.to_string(),
+                    kind,
+                    message: "scripted DNS failure".to_string(),
+                }),
+                None => Err(McError::DnsError {
+                    host: host.to_string(),
+                    kind: DnsErrorKind::NxDomain,
+                    message: "host not registered with MemoryTransport".to_string(),
+                }),
+            }
+        })
+    }
+
+    fn lookup_srv<'a>(
+        &'a self,
+        host: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SrvRecord>, McError>> {
+        Box::pin(async move { Ok(self.inner.srv.lock().unwrap().get(host).cloned()) })
+    }
+}
+
+/// Plays the server side of one scripted TCP connection.
+async fn serve_tcp(
+    mut stream: DuplexStream,
+    reply: Option<Reply>,
+    latency: Duration,
+    fault: Option<Fault>,
+) {
+    let mut decoder = FrameDecoder::new(DUPLEX_BUFFER);
+    let mut buf = [0u8; 1024];
+    let mut handshaken = false;
+
+    loop {
+        while let Ok(Some(packet)) = decoder.next_packet() {
+            // The handshake and the status request share packet ID 0x00.
+            let response = if !handshaken {
+                handshaken = java::Handshake::decode(&packet).is_ok();
+                continue;
+            } else if java::decode_status_request(&packet).is_ok() {
+                match &reply {
+                    Some(Reply::JavaStatus(json)) => java::encode_status_response(json),
+                    Some(Reply::Raw(bytes)) => bytes.clone(),
+                    Some(Reply::BedrockPong(_)) | None => continue,
+                }
+            } else if let Ok(payload) = java::decode_ping(&packet) {
+                java::encode_pong(payload)
+            } else {
+                continue;
+            };
+
+            match &fault {
+                Some(Fault::Hang) => continue,
+                Some(Fault::Close) => return,
+                _ => {}
+            }
+
+            sleep(latency).await;
+            let response = apply_fault(response, &fault);
+            if stream.write_all(&response).await.is_err() {
+                return;
+            }
+            if matches!(fault, Some(Fault::Truncate(_))) {
+                return;
+            }
+        }
+
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => decoder.push(&buf[..n]),
+        }
+    }
+}
+
+fn apply_fault(reply: Vec<u8>, fault: &Option<Fault>) -> Vec<u8> {
+    match fault {
+        Some(Fault::Truncate(n)) => reply[..(*n).min(reply.len())].to_vec(),
+        Some(Fault::Corrupt(bytes)) => bytes.clone(),
+        _ => reply,
+    }
+}
+
+type Delivery = (Instant, io::Result<(Vec<u8>, SocketAddr)>);
+
+struct MemoryDatagram {
+    transport: MemoryTransport,
+    sender: mpsc::UnboundedSender<Delivery>,
+    receiver: tokio::sync::Mutex<mpsc::UnboundedReceiver<Delivery>>,
+}
+
+impl Datagram for MemoryDatagram {
+    fn send_to<'a>(
+        &'a self,
+        buf: &'a [u8],
+        target: SocketAddr,
+    ) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(async move {
+            let (reply, latency, fault) = self.transport.attempt(target);
+            let deliver_at = Instant::now() + latency;
+
+            let response = match (&fault, reply) {
+                (Some(Fault::Hang | Fault::Close), _) | (_, None) => return Ok(buf.len()),
+                (Some(Fault::Refuse), _) => Err(io::ErrorKind::ConnectionRefused.into()),
+                (Some(Fault::Unreachable), _) => Err(io::ErrorKind::HostUnreachable.into()),
+                (_, Some(Reply::BedrockPong(server_id))) => {
+                    let time = bedrock::UnconnectedPing::decode(buf).map_or(0, |ping| ping.time);
+                    let pong = bedrock::UnconnectedPong {
+                        time,
+                        server_guid: u64::from(target.port()),
+                        server_id,
+                    };
+                    Ok((apply_fault(pong.encode(), &fault), target))
+                }
+                (_, Some(Reply::Raw(bytes))) => Ok((apply_fault(bytes, &fault), target)),
+                (_, Some(Reply::JavaStatus(_))) => return Ok(buf.len()),
+            };
+
+            let _ = self.sender.send((deliver_at, response));
+            Ok(buf.len())
+        })
+    }
+
+    fn recv_from<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, io::Result<(usize, SocketAddr)>> {
+        Box::pin(async move {
+            let mut receiver = self.receiver.lock().await;
+            let Some((deliver_at, response)) = receiver.recv().await else {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            };
+            sleep_until(deliver_at).await;
+
+            let (bytes, from) = response?;
+            let len = bytes.len().min(buf.len());
+            buf[..len].copy_from_slice(&bytes[..len]);
+            Ok((len, from))
+        })
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! Networking used by [`McClient`](crate::McClient), behind a trait so it can be replaced.
+//!
+//! [`TokioTransport`] is the default and talks to the real network. [`MemoryTransport`]
+//! serves scripted responses in-process for tests.
+
+mod memory;
+
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpStream, UdpSocket};
+use trust_dns_resolver::TokioAsyncResolver;
+use trust_dns_resolver::config::*;
+
+use crate::error::{DnsErrorKind, McError};
+
+pub use memory::{Fault, MemoryTransport};
+
+static DNS_CACHE: Lazy<DashMap<String, (Vec<IpAddr>, SystemTime)>> = Lazy::new(DashMap::new);
+static SRV_CACHE: Lazy<DashMap<String, (Option<SrvRecord>, SystemTime)>> = Lazy::new(DashMap::new);
+const DNS_CACHE_TTL: u64 = 300; // 5 minutes
+
+/// The target host and port of an SRV record.
+pub type SrvRecord = (String, u16);
+
+/// A bidirectional byte stream, such as a TCP connection.
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// A bound datagram socket.
+pub trait Datagram: Send + Sync {
+    fn send_to<'a>(&'a self, buf: &'a [u8], target: SocketAddr)
+    -> BoxFuture<'a, io::Result<usize>>;
+
+    fn recv_from<'a>(&'a self, buf: &'a mut [u8])
+    -> BoxFuture<'a, io::Result<(usize, SocketAddr)>>;
+}
+
+/// The network operations a ping needs. Timeouts and retries are applied by the client,
+/// so implementations only perform the operation itself.
+pub trait Transport: Send + Sync {
+    fn connect_tcp(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Stream>>>;
+
+    fn bind_udp(&self, local: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>>;
+
+    /// Resolves `host` to its IP addresses.
+    fn resolve<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Vec<IpAddr>, McError>>;
+
+    /// Looks up the `_minecraft._tcp` SRV record for `host`, returning the target host and
+    /// port of the preferred record, or `None` if there is no record.
+    fn lookup_srv<'a>(&'a self, host: &'a str)
+    -> BoxFuture<'a, Result<Option<SrvRecord>, McError>>;
+}
+
+/// The default transport: tokio sockets and the system resolver, with lookups cached
+/// process-wide for five minutes.
+///
+/// Addresses and SRV records come from the DNS servers in the system configuration, through
+/// a resolver that clones of this transport share. `/etc/hosts` is honoured, but other NSS
+/// sources such as mDNS or LDAP are not. It is built on first use and runs its I/O on
+/// the runtime in use at that point.
+#[derive(Clone, Default)]
+pub struct TokioTransport {
+    resolver: Arc<OnceCell<TokioAsyncResolver>>,
+}
+
+impl TokioTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn resolver(&self) -> &TokioAsyncResolver {
+        self.resolver.get_or_init(|| {
+            TokioAsyncResolver::tokio_from_system_conf().unwrap_or_else(|_| {
+                TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())
+            })
+        })
+    }
+}
+
+impl fmt::Debug for TokioTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokioTransport").finish_non_exhaustive()
+    }
+}
+
+impl Transport for TokioTransport {
+    fn connect_tcp(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Stream>>> {
+        Box::pin(async move {
+            let stream = TcpStream::connect(addr).await?;
+            stream.set_nodelay(true)?;
+            Ok(Box::new(stream) as Box<dyn Stream>)
+        })
+    }
+
+    fn bind_udp(&self, local: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>> {
+        Box::pin(async move {
+            let socket = UdpSocket::bind(local).await?;
+            Ok(Box::new(socket) as Box<dyn Datagram>)
+        })
+    }
+
+    fn resolve<'a>(&'a self, host: &'a str) -> BoxFuture<'a, Result<Vec<IpAddr>, McError>> {
+        Box::pin(async move {
+            // Check cache with TTL validation
+            if let Some(entry) = DNS_CACHE.get(host) {
+                let (addrs, timestamp) = entry.value().clone();
+                if is_fresh(timestamp) {
+                    return Ok(addrs);
+                }
+            }
+
+            let lookup = self
+                .resolver()
+                .lookup_ip(host)
+                .await
+                .map_err(|e| McError::dns(host, &e))?;
+            let mut addrs: Vec<IpAddr> = Vec::new();
+            for ip in lookup.iter() {
+                if !addrs.contains(&ip) {
+                    addrs.push(ip);
+                }
+            }
+
+            DNS_CACHE.insert(host.to_string(), (addrs.clone(), SystemTime::now()));
+            Ok(addrs)
+        })
+    }
+
+    fn lookup_srv<'a>(
+        &'a self,
+        host: &'a str,
+    ) -> BoxFuture<'a, Result<Option<SrvRecord>, McError>> {
+        Box::pin(async move {
+            let srv_name = format!("_minecraft._tcp.{}", host);
+
+            // Check cache with TTL validation
+            if let Some(entry) = SRV_CACHE.get(&srv_name) {
+                let (record, timestamp) = entry.value().clone();
+                if is_fresh(timestamp) {
+                    return Ok(record);
+                }
+            }
+
+            let record = match self.resolver().srv_lookup(&srv_name).await {
+                // Get the first SRV record with the highest priority (lowest number)
+                Ok(response) => response.iter().min_by_key(|r| r.priority()).map(|srv| {
+                    // Remove trailing dot from target if present
+                    let target = srv.target().to_utf8();
+                    (target.trim_end_matches('.').to_string(), srv.port())
+                }),
+                Err(e) => match McError::dns(&srv_name, &e) {
+                    McError::DnsError {
+                        kind: DnsErrorKind::NxDomain | DnsErrorKind::NoRecords,
+                        ..
+                    } => None,
+                    other => return Err(other),
+                },
+            };
+
+            SRV_CACHE.insert(srv_name, (record.clone(), SystemTime::now()));
+            Ok(record)
+        })
+    }
+}
+
+impl Datagram for UdpSocket {
+    fn send_to<'a>(
+        &'a self,
+        buf: &'a [u8],
+        target: SocketAddr,
+    ) -> BoxFuture<'a, io::Result<usize>> {
+        Box::pin(UdpSocket::send_to(self, buf, target))
+    }
+
+    fn recv_from<'a>(
+        &'a self,
+        buf: &'a mut [u8],
+    ) -> BoxFuture<'a, io::Result<(usize, SocketAddr)>> {
+        Box::pin(UdpSocket::recv_from(self, buf))
+    }
+}
+
+fn is_fresh(timestamp: SystemTime) -> bool {
+    timestamp
+        .elapsed()
+        .map(|d| d.as_secs() < DNS_CACHE_TTL)
+        .unwrap_or(false)
+}
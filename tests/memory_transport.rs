@@ -0,0 +1,308 @@
+// Copyright (c) 2025 pynickle. This is a fork of Original Crate. Original copyright: Copyright (c) 2025 NameOfShadow
+
+//! End-to-end client behaviour against scripted servers on a [`MemoryTransport`].
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use mc_server_status::scan::Scan;
+use mc_server_status::transport::Fault;
+use mc_server_status::{
+    ConnectionErrorKind, DnsErrorKind, ErrorKind, McClient, McError, MemoryTransport, Phase,
+    RateLimits, RetryPolicy, ServerData, ServerEdition, ServerInfo,
+};
+
+const STATUS: &str = r#"{"version":{"name":"1.21","protocol":767},"players":{"max":20,"online":3},"description":"A test server"}"#;
+
+fn addr() -> SocketAddr {
+    "10.0.0.1:25565".parse().unwrap()
+}
+
+fn java_server() -> MemoryTransport {
+    MemoryTransport::new()
+        .with_host("mc.test", [addr().ip()])
+        .with_java_status(addr(), STATUS)
+}
+
+fn client(transport: &MemoryTransport) -> McClient {
+    McClient::new()
+        .with_timeout(Duration::from_secs(2))
+        .with_retry(RetryPolicy::none())
+        .with_transport(transport.clone())
+}
+
+fn quick_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(max_attempts).with_backoff(Duration::from_millis(1), Duration::from_millis(1))
+}
+
+#[tokio::test]
+async fn pings_scripted_java_server() {
+    let transport = java_server();
+    let status = client(&transport).ping_java("mc.test").await.unwrap();
+
+    assert_eq!(status.port, 25565);
+    assert_eq!(status.attempts, 1);
+    match status.data {
+        ServerData::Java(java) => {
+            assert_eq!(java.version.name, "1.21");
+            assert_eq!(java.players.online, 3);
+        }
+        ServerData::Bedrock(_) => panic!("expected a Java status"),
+    }
+}
+
+#[tokio::test]
+async fn retries_transient_faults() {
+    let transport = java_server().with_faults_once(addr(), [Fault::Hang, Fault::Hang]);
+    let status = client(&transport)
+        .with_read_timeout(Duration::from_millis(50))
+        .with_retry(quick_retries(3))
+        .ping_java("mc.test")
+        .await
+        .unwrap();
+
+    assert_eq!(status.attempts, 3);
+    assert_eq!(transport.attempts(addr()), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let transport = java_server().with_fault(addr(), Fault::Close);
+    let error = client(&transport)
+        .with_retry(quick_retries(2))
+        .ping_java("mc.test")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        McError::ConnectionError {
+            phase: Phase::Read,
+            kind: ConnectionErrorKind::Closed,
+            ..
+        }
+    ));
+    assert!(error.is_retryable());
+    assert!(error.is_offline());
+    assert_eq!(transport.attempts(addr()), 2);
+}
+
+#[tokio::test]
+async fn does_not_retry_permanent_errors() {
+    let transport = java_server().with_fault(addr(), Fault::Refuse);
+    let result = client(&transport)
+        .with_retry(quick_retries(3))
+        .probe(&ServerInfo::new("mc.test", ServerEdition::Java))
+        .await;
+
+    assert_eq!(result.attempts, 1);
+    assert_eq!(transport.attempts(addr()), 1);
+}
+
+#[tokio::test]
+async fn classifies_dns_failures() {
+    let transport = java_server().with_dns_error("broken.test", DnsErrorKind::ServFail);
+    let client = client(&transport);
+
+    let error = client.ping_java("broken.test").await.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Dns);
+    assert_eq!(error.phase(), Some(Phase::Resolve));
+    assert!(error.is_retryable());
+    assert!(error.is_offline());
+
+    let record = client
+        .ping_java("missing.test")
+        .await
+        .unwrap_err()
+        .to_record();
+    assert_eq!(record.dns, Some(DnsErrorKind::NxDomain));
+    assert_eq!(record.host.as_deref(), Some("missing.test"));
+    assert!(!record.retryable);
+}
+
+#[tokio::test]
+async fn classifies_connection_failures() {
+    let transport = java_server().with_fault(addr(), Fault::Refuse);
+    let record = client(&transport)
+        .ping_java("mc.test")
+        .await
+        .unwrap_err()
+        .to_record();
+
+    assert_eq!(record.kind, ErrorKind::Connection);
+    assert_eq!(record.phase, Some(Phase::Connect));
+    assert_eq!(record.address, Some(addr()));
+    assert_eq!(record.connection, Some(ConnectionErrorKind::Refused));
+    assert!(record.offline);
+}
+
+#[tokio::test]
+async fn classifies_bad_responses() {
+    let transport = MemoryTransport::new()
+        .with_host("mc.test", [addr().ip()])
+        .with_java_status(addr(), "{not json");
+    let client = client(&transport);
+
+    let result = client
+        .probe(&ServerInfo::new("mc.test", ServerEdition::Java))
+        .await;
+    let reason = result.offline_reason().unwrap();
+    assert_eq!(reason.kind, ErrorKind::Json);
+    assert!(!reason.offline);
+    // Failures after resolution still report where the server was.
+    assert_eq!(result.address, Some(addr()));
+    assert!(result.dns.is_some());
+
+    let corrupt = java_server().with_fault(addr(), Fault::Corrupt(vec![0xFF; 6]));
+    let error = self::client(&corrupt)
+        .ping_java("mc.test")
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Protocol);
+}
+
+#[tokio::test]
+async fn times_out_waiting_for_reply() {
+    let transport = java_server().with_fault(addr(), Fault::Hang);
+    let error = client(&transport)
+        .with_read_timeout(Duration::from_millis(50))
+        .ping_java("mc.test")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error,
+        McError::Timeout {
+            phase: Phase::Read,
+            addr: Some(a),
+        } if a == addr()
+    ));
+}
+
+#[tokio::test]
+async fn deadline_covers_retries() {
+    let transport = java_server().with_fault(addr(), Fault::Hang);
+    let started = Instant::now();
+    let error = client(&transport)
+        .with_read_timeout(Duration::from_millis(100))
+        .with_deadline(Duration::from_millis(250))
+        .with_retry(quick_retries(10))
+        .ping_java("mc.test")
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::Timeout);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(transport.attempts(addr()) <= 3);
+}
+
+#[tokio::test]
+async fn rate_limit_waits_do_not_use_connect_timeout() {
+    let transport = java_server();
+    let client = client(&transport)
+        .with_connect_timeout(Duration::from_millis(50))
+        .with_rate_limits(RateLimits::new().with_min_host_spacing(Duration::from_millis(200)));
+
+    client.ping_java("mc.test").await.unwrap();
+    client.ping_java("mc.test").await.unwrap();
+    assert_eq!(transport.attempts(addr()), 2);
+}
+
+#[tokio::test]
+async fn rejects_oversized_responses() {
+    let large = format!(
+        r#"{{"version":{{"name":"1.21","protocol":767}},"description":"{}"}}"#,
+        "x".repeat(4096)
+    );
+    let transport = MemoryTransport::new()
+        .with_host("mc.test", [addr().ip()])
+        .with_java_status(addr(), large);
+
+    let error = client(&transport)
+        .with_max_response_size(1024)
+        .ping_java("mc.test")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        McError::ResponseTooLarge { limit: 1024, .. }
+    ));
+    assert_eq!(error.kind(), ErrorKind::Protocol);
+
+    let status = client(&transport)
+        .with_max_response_size(8192)
+        .ping_java("mc.test")
+        .await;
+    assert!(status.is_ok());
+}
+
+#[tokio::test]
+async fn reads_long_bedrock_pongs() {
+    let motd = "m".repeat(4000);
+    let server_id = format!("MCPE;{motd};712;1.21.0;1;10;123;World;Survival;1;19132;19133;");
+    let addr: SocketAddr = "10.0.0.1:19132".parse().unwrap();
+    let transport = MemoryTransport::new()
+        .with_host("mc.test", [addr.ip()])
+        .with_bedrock_pong(addr, server_id);
+
+    let status = client(&transport).ping_bedrock("mc.test").await.unwrap();
+    match status.data {
+        ServerData::Bedrock(bedrock) => assert_eq!(bedrock.motd, motd),
+        ServerData::Java(_) => panic!("expected a Bedrock status"),
+    }
+}
+
+#[tokio::test]
+async fn rejects_truncated_bedrock_pongs() {
+    let addr: SocketAddr = "10.0.0.1:19132".parse().unwrap();
+    let transport = MemoryTransport::new()
+        .with_host("mc.test", [addr.ip()])
+        .with_bedrock_pong(addr, "MCPE;Hello;712;1.21.0;1;10;")
+        .with_fault(addr, Fault::Truncate(40));
+
+    let error = client(&transport)
+        .ping_bedrock("mc.test")
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Protocol);
+}
+
+#[tokio::test]
+async fn resumed_scan_skips_completed_targets() {
+    let addrs: Vec<SocketAddr> = (0..16)
+        .map(|host| format!("10.0.0.{host}:25565").parse().unwrap())
+        .collect();
+    let transport = addrs
+        .iter()
+        .fold(MemoryTransport::new(), |transport, addr| {
+            transport.with_java_status(*addr, STATUS)
+        });
+    // One ping at a time and a save after each, so nothing is in flight when the scan stops.
+    let client = client(&transport)
+        .with_max_parallel(1)
+        .with_checkpoint_interval(Duration::ZERO);
+    let scan = Scan::new().with_cidr("10.0.0.0/28").unwrap().with_seed(7);
+    let checkpoint = std::env::temp_dir().join(format!("mc-scan-{}.ndjson", std::process::id()));
+
+    let first: Vec<_> = client
+        .scan_resumable(&scan, &checkpoint)
+        .await
+        .unwrap()
+        .take(5)
+        .collect()
+        .await;
+    assert_eq!(first.len(), 5);
+    assert!(checkpoint.exists());
+
+    let all: Vec<_> = client
+        .scan_resumable(&scan, &checkpoint)
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert_eq!(all.len(), 16);
+    assert!(all.iter().all(|hit| hit.is_ok()));
+    assert!(addrs.iter().all(|addr| transport.attempts(*addr) == 1));
+    assert!(!checkpoint.exists());
+}